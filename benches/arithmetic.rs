@@ -0,0 +1,103 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand::thread_rng;
+use rand::Rng;
+use rgsw::bfv::*;
+use rgsw::poly::*;
+use rgsw::residue::*;
+use rgsw::rgsw::*;
+use rgsw::rlwe::*;
+use rgsw::transpose::*;
+
+fn residue_benches(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let a: Residue = rng.gen();
+    let b: Residue = rng.gen();
+    c.bench_function("residue add", |bench| {
+        bench.iter(|| black_box(a) + black_box(b))
+    });
+    c.bench_function("residue sub", |bench| {
+        bench.iter(|| black_box(a) - black_box(b))
+    });
+    c.bench_function("residue mul", |bench| {
+        bench.iter(|| black_box(a) * black_box(b))
+    });
+    c.bench_function("residue pow", |bench| {
+        bench.iter(|| black_box(a).pow(black_box(65537)))
+    });
+    c.bench_function("residue inv", |bench| bench.iter(|| black_box(a).inv()));
+}
+
+fn crt_benches(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let mut scratch = Scratch::new();
+    let power: PowerPoly = rng.gen();
+    let chrem: ChremPoly = rng.gen();
+    c.bench_function("power to crt", |bench| {
+        bench.iter_batched(
+            || power.clone(),
+            |power| power.into_crt_in_place(&mut scratch),
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("crt to power", |bench| {
+        bench.iter_batched(
+            || chrem.clone(),
+            |chrem| chrem.into_power_in_place(&mut scratch),
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("crt mul", |bench| {
+        bench.iter_batched(
+            || chrem.clone(),
+            |product| product * &chrem,
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn transpose_benches(c: &mut Criterion) {
+    let src: Vec<Residue> = (0..DEGREE).map(|i| Residue::from_u64(i as u64)).collect();
+    let mut dest = vec![Residue::zero(); DEGREE];
+    c.bench_function("transpose 128x128", |bench| {
+        bench.iter(|| transpose(128, DEGREE / 128, &src, &mut dest).unwrap())
+    });
+    c.bench_function("transpose 2x8192", |bench| {
+        bench.iter(|| transpose(2, DEGREE / 2, &src, &mut dest).unwrap())
+    });
+    let mut data = src.clone();
+    c.bench_function("transpose 128x128 in place", |bench| {
+        bench.iter(|| transpose_in_place(128, &mut data, 128, DEFAULT_BLOCK_SIZE).unwrap())
+    });
+}
+
+fn rlwe_benches(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let other_key = SecretKey::generate(&mut rng);
+    let message: PowerPoly = rng.gen();
+    c.bench_function("rlwe encrypt", |bench| {
+        bench.iter(|| RlweCiphertext::encrypt(&key, &message, &mut rng))
+    });
+    let ciphertext = RlweCiphertext::encrypt(&key, &message, &mut rng);
+    let switching_key = KeySwitchingKey::generate(&key, &other_key, &mut rng);
+    let mut scratch = Scratch::new();
+    c.bench_function("key switch", |bench| {
+        bench.iter(|| switching_key.switch_with(&ciphertext, &mut scratch))
+    });
+    let rgsw = RgswCiphertext::encrypt(&key, &PowerPoly::monomial(1), &mut rng);
+    c.bench_function("external product", |bench| {
+        bench.iter(|| rgsw.external_product_with(&ciphertext, &mut scratch))
+    });
+    let bfv = Bfv::new(65537).unwrap();
+    let relinearization_key = RelinearizationKey::generate(&key, &mut rng);
+    c.bench_function("bfv mul", |bench| {
+        bench.iter(|| bfv.mul_with(&ciphertext, &ciphertext, &relinearization_key, &mut scratch))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = residue_benches, crt_benches, transpose_benches, rlwe_benches
+}
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rgsw::poly::*;
+use rgsw::rlwe::*;
+
+// Short inputs are repeated up to the encoding length, so that the fuzzer reaches the residue
+// checks without producing megabytes of input.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(poly) = PowerPoly::from_bytes(data) {
+        assert_eq!(poly.to_bytes(), data);
+    }
+    if data.is_empty() {
+        return;
+    }
+    let repeated: Vec<u8> = data.iter().cycle().take(POLY_BYTES).cloned().collect();
+    if let Ok(poly) = PowerPoly::from_bytes(&repeated) {
+        assert_eq!(poly.to_bytes(), repeated);
+    }
+    if let Ok(poly) = ChremPoly::from_bytes(&repeated) {
+        assert_eq!(poly.to_bytes(), repeated);
+    }
+    let repeated: Vec<u8> = data.iter().cycle().take(2 * POLY_BYTES).cloned().collect();
+    if let Ok(ciphertext) = RlweCiphertext::from_bytes(&repeated) {
+        assert_eq!(ciphertext.to_bytes(), repeated);
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rgsw::residue::*;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(residue) = Residue::from_bytes(data) {
+        assert!(residue.to_u384() < MODULUS);
+        assert_eq!(&residue.to_bytes()[..], data);
+    }
+});
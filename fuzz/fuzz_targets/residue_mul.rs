@@ -0,0 +1,21 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rgsw::residue::*;
+use std::convert::TryFrom;
+
+// Differential test of the fast reduction against U768 division
+fuzz_target!(|data: &[u8]| {
+    if data.len() != 2 * RESIDUE_BYTES {
+        return;
+    }
+    let reduce =
+        |bytes: &[u8]| Residue::try_from(U384::from_little_endian(bytes) % MODULUS).unwrap();
+    let a = reduce(&data[..RESIDUE_BYTES]);
+    let b = reduce(&data[RESIDUE_BYTES..]);
+    let expected = (U768::from(a.to_u384()) * U768::from(b.to_u384())) % U768::from(MODULUS);
+    let expected = Residue::try_from(U384::try_from(expected).unwrap()).unwrap();
+    assert_eq!(a * b, expected);
+    let mut sum = Accumulator::zero();
+    sum.mul_acc(a, b);
+    assert_eq!(sum.reduce(), expected);
+});
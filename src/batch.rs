@@ -0,0 +1,205 @@
+use crate::error::Error;
+use crate::modulus::*;
+use crate::parallel::Schedule;
+use crate::poly::*;
+use crate::residue::*;
+use crate::rlwe::*;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::{Mul, MulAssign, Sub};
+
+// Plaintext batching for a prime plaintext modulus t = M::VALUE with t = 1 mod 2 * DEGREE: Z_t
+// then has a primitive 2 * DEGREE-th root of unity ROOT, and Z_t[X]/(X^DEGREE + 1) splits into
+// DEGREE slots, where slot i holds the evaluation at ROOT^(2i + 1) as for ChremPoly. Products of
+// plaintexts are slot-wise products.
+pub struct BatchEncoder<M> {
+    // ROOT^e for e in [2 * DEGREE]
+    roots: Vec<WordResidue<M>>,
+}
+
+// Residue modulo M::VALUE
+struct WordResidue<M>(u64, PhantomData<fn() -> M>);
+
+impl<M: Modulus> BatchEncoder<M> {
+    pub fn new() -> Result<BatchEncoder<M>, Error> {
+        let () = M::CHECK;
+        let t = M::VALUE;
+        let index = INDEX_BASE.pow(INDEX_POWER);
+        if !is_prime(t) || !(t - 1).is_multiple_of(index as u64) {
+            return Err(Error::ParameterMismatch(
+                "batching needs a prime plaintext modulus which is 1 modulo 2 * DEGREE",
+            ));
+        }
+        // g^((t - 1) / index) is primitive iff its power DEGREE is -1, which holds for half of
+        // all g.
+        let root = (2..t)
+            .map(|g| WordResidue::<M>::new(g).pow((t - 1) / index as u64))
+            .find(|root| root.pow(DEGREE as u64).0 == t - 1)
+            .expect("a prime modulus has a primitive root");
+        let roots = (0..index)
+            .scan(WordResidue::new(1), |power, _| {
+                let current = *power;
+                *power *= root;
+                Some(current)
+            })
+            .collect();
+        Ok(BatchEncoder { roots })
+    }
+
+    // Pads the slots with zeroes.
+    pub fn encode(&self, slots: &[u64]) -> Result<Plaintext, Error> {
+        if slots.len() > DEGREE {
+            return Err(Error::LengthMismatch {
+                expected: DEGREE,
+                actual: slots.len(),
+            });
+        }
+        if slots.iter().any(|&slot| slot >= M::VALUE) {
+            return Err(Error::OutOfRange);
+        }
+        let mut data = vec![WordResidue::new(0); DEGREE];
+        for (residue, &slot) in data.iter_mut().zip(slots.iter()) {
+            *residue = WordResidue::new(slot);
+        }
+        let mut buffer = vec![WordResidue::new(0); DEGREE];
+        inv_crt(
+            INDEX_BASE,
+            INDEX_POWER,
+            &self.roots,
+            &mut data,
+            &mut buffer,
+            Schedule::Parallel,
+        );
+        let coeffs: Vec<u64> = data.iter().map(|residue| residue.0).collect();
+        Plaintext::new(M::VALUE, &coeffs)
+    }
+
+    pub fn decode(&self, plaintext: &Plaintext) -> Result<Vec<u64>, Error> {
+        if plaintext.modulus() != M::VALUE {
+            return Err(Error::ParameterMismatch(
+                "the plaintext modulus doesn't match the encoder's",
+            ));
+        }
+        let mut data: Vec<WordResidue<M>> = plaintext
+            .coeffs()
+            .iter()
+            .map(|&coeff| WordResidue::new(coeff))
+            .collect();
+        let mut buffer = vec![WordResidue::new(0); DEGREE];
+        crt(
+            INDEX_BASE,
+            INDEX_POWER,
+            &self.roots,
+            &mut data,
+            &mut buffer,
+            Schedule::Parallel,
+        );
+        Ok(data.iter().map(|residue| residue.0).collect())
+    }
+}
+
+impl<M: Modulus> WordResidue<M> {
+    fn new(x: u64) -> WordResidue<M> {
+        WordResidue(x % M::VALUE, PhantomData)
+    }
+
+    fn pow(self, mut exponent: u64) -> WordResidue<M> {
+        let mut base = self;
+        let mut result = WordResidue::new(1);
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent /= 2;
+        }
+        result
+    }
+}
+
+impl<M> Clone for WordResidue<M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for WordResidue<M> {}
+
+impl<M: Modulus> Sub for WordResidue<M> {
+    type Output = WordResidue<M>;
+
+    fn sub(self, other: WordResidue<M>) -> WordResidue<M> {
+        WordResidue::new(self.0 + M::VALUE - other.0)
+    }
+}
+
+impl<M: Modulus> MulAssign for WordResidue<M> {
+    fn mul_assign(&mut self, other: WordResidue<M>) {
+        self.0 = (self.0 as u128 * other.0 as u128 % M::VALUE as u128) as u64;
+    }
+}
+
+impl<M: Modulus> Mul for WordResidue<M> {
+    type Output = WordResidue<M>;
+
+    fn mul(mut self, other: WordResidue<M>) -> WordResidue<M> {
+        self *= other;
+        self
+    }
+}
+
+impl<M: Modulus> TransformRing for WordResidue<M> {
+    fn from_u64(x: u64) -> WordResidue<M> {
+        WordResidue::new(x)
+    }
+
+    // Fermat's little theorem, since the modulus is prime.
+    fn inv(self) -> WordResidue<M> {
+        self.pow(M::VALUE - 2)
+    }
+
+    fn dot<I: Iterator<Item = (Self, Self)>>(terms: I) -> WordResidue<M> {
+        let t = M::VALUE as u128;
+        let sum = terms.fold(0, |sum, (a, b)| (sum + a.0 as u128 * b.0 as u128) % t);
+        WordResidue(sum as u64, PhantomData)
+    }
+}
+
+// Deterministic Miller-Rabin test, exact for all 64-bit numbers with these bases
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(&base) = BASES.iter().find(|&&base| n.is_multiple_of(base)) {
+        return n == base;
+    }
+    let mul_mod = |a: u64, b: u64| (a as u128 * b as u128 % n as u128) as u64;
+    let pow_mod = |mut base: u64, mut exponent: u64| {
+        let mut result = 1;
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = mul_mod(result, base);
+            }
+            base = mul_mod(base, base);
+            exponent /= 2;
+        }
+        result
+    };
+    let shift = (n - 1).trailing_zeros();
+    let odd = (n - 1) >> shift;
+    BASES.iter().all(|&base| {
+        let mut x = pow_mod(base, odd);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..shift {
+            x = mul_mod(x, x);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
@@ -0,0 +1,188 @@
+use crate::error::Error;
+use crate::poly::*;
+use crate::residue::*;
+use crate::rlwe::*;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use rand::Rng;
+
+// The BFV scheme over the same ring: a ciphertext is an RlweCiphertext whose phase is
+// floor(MODULUS / t) * m + e for a message m in Z_t[X]/(X^DEGREE + 1).
+
+// Centered coefficients are split into signed digits of DIGIT_BITS bits. A product of two digit
+// polynomials has coefficients below DEGREE * 2^(2 * DIGIT_BITS), so sums of a few of them are
+// exact in the ring.
+const DIGIT_BITS: usize = 128;
+const DIGITS: usize = 3;
+
+// Parameters of the scheme, i.e. the plaintext modulus t
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bfv {
+    plaintext_modulus: u64,
+}
+
+// Key switching key from s^2 to s
+pub struct RelinearizationKey(KeySwitchingKey);
+
+impl RelinearizationKey {
+    pub fn generate<R: Rng + ?Sized>(key: &SecretKey, rng: &mut R) -> RelinearizationKey {
+        RelinearizationKey(KeySwitchingKey::generate(&key.squared(), key, rng))
+    }
+}
+
+impl Bfv {
+    pub fn new(plaintext_modulus: u64) -> Result<Bfv, Error> {
+        check_plaintext_modulus(plaintext_modulus)?;
+        Ok(Bfv { plaintext_modulus })
+    }
+
+    pub fn plaintext_modulus(&self) -> u64 {
+        self.plaintext_modulus
+    }
+
+    pub fn encrypt<R: Rng + ?Sized>(
+        &self,
+        key: &SecretKey,
+        plaintext: &Plaintext,
+        rng: &mut R,
+    ) -> Result<RlweCiphertext, Error> {
+        self.check_plaintext(plaintext)?;
+        Ok(RlweCiphertext::encrypt(key, &plaintext.encode(), rng))
+    }
+
+    pub fn decrypt(&self, key: &SecretKey, ciphertext: &RlweCiphertext) -> Plaintext {
+        Plaintext::decode(&ciphertext.phase(key), self.plaintext_modulus)
+            .expect("the plaintext modulus was checked on construction")
+    }
+
+    pub fn add(&self, a: &RlweCiphertext, b: &RlweCiphertext) -> RlweCiphertext {
+        let mut result = a.clone();
+        result += b;
+        result
+    }
+
+    pub fn add_plain(
+        &self,
+        ciphertext: &RlweCiphertext,
+        plaintext: &Plaintext,
+    ) -> Result<RlweCiphertext, Error> {
+        self.check_plaintext(plaintext)?;
+        let mut result = ciphertext.clone();
+        result.b += &plaintext.encode();
+        Ok(result)
+    }
+
+    pub fn mul(
+        &self,
+        a: &RlweCiphertext,
+        b: &RlweCiphertext,
+        key: &RelinearizationKey,
+    ) -> RlweCiphertext {
+        self.mul_with(a, b, key, &mut Scratch::new())
+    }
+
+    // Scales the tensor product by t / MODULUS and relinearizes its s^2 component.
+    pub fn mul_with(
+        &self,
+        a: &RlweCiphertext,
+        b: &RlweCiphertext,
+        key: &RelinearizationKey,
+        scratch: &mut Scratch,
+    ) -> RlweCiphertext {
+        // (a.b - a.a s)(b.b - b.a s) = d0 - d1 s + d2 s^2
+        let a_b = signed_digits(&a.b, scratch);
+        let a_a = signed_digits(&a.a, scratch);
+        let b_b = signed_digits(&b.b, scratch);
+        let b_a = signed_digits(&b.a, scratch);
+        let d0 = self.scaled_product(&[(&a_b, &b_b)], scratch);
+        let d1 = self.scaled_product(&[(&a_b, &b_a), (&a_a, &b_b)], scratch);
+        let d2 = self.scaled_product(&[(&a_a, &b_a)], scratch);
+        // An encryption of d2 s^2 under s^2 has phase 0 - (-d2) s^2.
+        let squared = RlweCiphertext {
+            a: -d2,
+            b: PowerPoly::zero(),
+        };
+        let mut result = key.0.switch_with(&squared, scratch);
+        result.a += &d1;
+        result.b += &d0;
+        result
+    }
+
+    fn check_plaintext(&self, plaintext: &Plaintext) -> Result<(), Error> {
+        if plaintext.modulus() != self.plaintext_modulus {
+            return Err(Error::ParameterMismatch(
+                "the plaintext modulus doesn't match the scheme's",
+            ));
+        }
+        Ok(())
+    }
+
+    // round(t / MODULUS * sum of x * y) for the integer products of the centered polynomials,
+    // reduced modulo MODULUS
+    fn scaled_product(
+        &self,
+        pairs: &[(&Vec<ChremPoly>, &Vec<ChremPoly>)],
+        scratch: &mut Scratch,
+    ) -> PowerPoly {
+        // Diagonal k collects the digit products of weight 2^(k * DIGIT_BITS).
+        let diagonals: Vec<PowerPoly> = (0..2 * DIGITS - 1)
+            .map(|k| {
+                let products = pairs.iter().flat_map(|(x, y)| {
+                    (0..DIGITS)
+                        .filter(move |&i| k >= i && k - i < DIGITS)
+                        .map(move |i| (&x[i], &y[k - i]))
+                });
+                ChremPoly::inner_product(products).into_power_in_place(scratch)
+            })
+            .collect();
+        let q = U1024::from(MODULUS);
+        let t = U1024::from(self.plaintext_modulus);
+        let mut result = PowerPoly::zero();
+        for (i, coeff) in result.iter_mut().enumerate() {
+            let mut positive = U1024::zero();
+            let mut negative = U1024::zero();
+            for (k, diagonal) in diagonals.iter().enumerate() {
+                let centered = diagonal[i].to_centered();
+                let shifted = U1024::from(centered.magnitude) << (k * DIGIT_BITS);
+                if centered.negative {
+                    negative += shifted;
+                } else {
+                    positive += shifted;
+                }
+            }
+            let (negative, magnitude) = if positive >= negative {
+                (false, positive - negative)
+            } else {
+                (true, negative - positive)
+            };
+            let rounded = ((magnitude * t + (q >> 1)) / q) % q;
+            *coeff = Residue::from(Centered {
+                negative,
+                magnitude: U384::try_from(rounded).expect("reduced modulo MODULUS"),
+            });
+        }
+        result
+    }
+}
+
+// The centered coefficients as sums of signed digits times 2^(k * DIGIT_BITS), in CRT
+// representation
+fn signed_digits(poly: &PowerPoly, scratch: &mut Scratch) -> Vec<ChremPoly> {
+    (0..DIGITS)
+        .map(|k| {
+            let mut digit = PowerPoly::zero();
+            for (residue, coeff) in digit.iter_mut().zip(poly.iter()) {
+                let centered = coeff.to_centered();
+                // Each digit is two 64-bit limbs of the magnitude.
+                let limbs = centered.magnitude.0;
+                let mut magnitude = [0; 6];
+                magnitude[..2].copy_from_slice(&limbs[2 * k..2 * k + 2]);
+                *residue = Residue::from(Centered {
+                    negative: centered.negative,
+                    magnitude: U384(magnitude),
+                });
+            }
+            digit.into_crt_in_place(scratch)
+        })
+        .collect()
+}
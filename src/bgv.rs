@@ -0,0 +1,266 @@
+use crate::error::Error;
+use crate::modulus::*;
+use crate::poly::*;
+use crate::residue::*;
+use crate::rlwe::*;
+use alloc::vec::Vec;
+use core::iter::once;
+use core::marker::PhantomData;
+use rand::Rng;
+use zeroize::Zeroizing;
+
+// The BGV scheme over word-sized moduli: a ciphertext modulo M has phase m + t * e for a message
+// m in Z_t[X]/(X^DEGREE + 1). The levels of a modulus chain are Modulus types, and ciphertexts
+// move down the chain by modulus switching, typically after each multiplication. Ring products
+// are computed on centered lifts in the CRT representation, which is exact.
+
+// Key switching decomposes the canonical coefficients, which are below 2^63, into DIGITS digits.
+const DIGIT_BITS: usize = 16;
+const DIGITS: usize = 4;
+
+// Parameters of the scheme, i.e. the plaintext modulus t
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bgv {
+    plaintext_modulus: u64,
+}
+
+pub struct BgvCiphertext<M> {
+    pub a: ModPoly<M>,
+    pub b: ModPoly<M>,
+}
+
+impl<M> Clone for BgvCiphertext<M> {
+    fn clone(&self) -> Self {
+        BgvCiphertext {
+            a: self.a.clone(),
+            b: self.b.clone(),
+        }
+    }
+}
+
+// DIGITS rows with b_k = a_k s_to + t e_k + 2^(k * DIGIT_BITS) s_from, as lifts in the CRT
+// representation
+pub struct BgvKeySwitchingKey<M> {
+    rows: Vec<(ChremPoly, ChremPoly)>,
+    modulus: PhantomData<M>,
+}
+
+impl Bgv {
+    pub fn new(plaintext_modulus: u64) -> Result<Bgv, Error> {
+        check_plaintext_modulus(plaintext_modulus)?;
+        Ok(Bgv { plaintext_modulus })
+    }
+
+    pub fn plaintext_modulus(&self) -> u64 {
+        self.plaintext_modulus
+    }
+
+    pub fn encrypt<M: Modulus, R: Rng + ?Sized>(
+        &self,
+        key: &SecretKey,
+        plaintext: &Plaintext,
+        rng: &mut R,
+    ) -> Result<BgvCiphertext<M>, Error> {
+        if plaintext.modulus() != self.plaintext_modulus {
+            return Err(Error::ParameterMismatch(
+                "the plaintext modulus doesn't match the scheme's",
+            ));
+        }
+        self.check_modulus::<M>()?;
+        let message = ModPoly::from_coeffs(plaintext.coeffs())?;
+        let a: ModPoly<M> = rng.gen();
+        let b = self.encrypt_zero(key.power(), &a, rng) + &message;
+        Ok(BgvCiphertext { a, b })
+    }
+
+    pub fn decrypt<M: Modulus>(&self, key: &SecretKey, ciphertext: &BgvCiphertext<M>) -> Plaintext {
+        let key = Zeroizing::new(ModPoly::<M>::reduce(key.power()));
+        let a_s = Zeroizing::new(ciphertext.a.clone() * &key);
+        let phase = Zeroizing::new(ciphertext.b.clone() - &a_s);
+        let t = self.plaintext_modulus as i64;
+        let coeffs: Vec<u64> = phase.centered().map(|x| x.rem_euclid(t) as u64).collect();
+        Plaintext::new(self.plaintext_modulus, &coeffs)
+            .expect("the coefficients are reduced modulo the plaintext modulus")
+    }
+
+    pub fn add<M: Modulus>(&self, x: &BgvCiphertext<M>, y: &BgvCiphertext<M>) -> BgvCiphertext<M> {
+        BgvCiphertext {
+            a: x.a.clone() + &y.a,
+            b: x.b.clone() + &y.b,
+        }
+    }
+
+    pub fn mul<M: Modulus>(
+        &self,
+        x: &BgvCiphertext<M>,
+        y: &BgvCiphertext<M>,
+        relinearization_key: &BgvKeySwitchingKey<M>,
+    ) -> BgvCiphertext<M> {
+        self.mul_with(x, y, relinearization_key, &mut Scratch::new())
+    }
+
+    // Multiplies the phases and relinearizes with a key switching key from s^2 to s.
+    pub fn mul_with<M: Modulus>(
+        &self,
+        x: &BgvCiphertext<M>,
+        y: &BgvCiphertext<M>,
+        relinearization_key: &BgvKeySwitchingKey<M>,
+        scratch: &mut Scratch,
+    ) -> BgvCiphertext<M> {
+        let [x_a, x_b, y_a, y_b] =
+            [&x.a, &x.b, &y.a, &y.b].map(|poly| poly.lift().into_crt_in_place(scratch));
+        // (x.b - x.a s)(y.b - y.a s) = d0 - d1 s + d2 s^2
+        let d0 = ChremPoly::inner_product(once((&x_b, &y_b)));
+        let d1 = ChremPoly::inner_product([(&x_a, &y_b), (&x_b, &y_a)].iter().copied());
+        let d2 = ChremPoly::inner_product(once((&x_a, &y_a)));
+        let [d0, d1, d2] =
+            [d0, d1, d2].map(|poly| ModPoly::reduce(&poly.into_power_in_place(scratch)));
+        // An encryption of d2 s^2 under s^2 has phase 0 - (-d2) s^2.
+        let squared = BgvCiphertext {
+            a: -d2,
+            b: ModPoly::zero(),
+        };
+        let mut result = self.key_switch_with(&squared, relinearization_key, scratch);
+        result.a += &d1;
+        result.b += &d0;
+        result
+    }
+
+    pub fn key_switching_key<M: Modulus, R: Rng + ?Sized>(
+        &self,
+        from: &SecretKey,
+        to: &SecretKey,
+        rng: &mut R,
+    ) -> Result<BgvKeySwitchingKey<M>, Error> {
+        self.key_switching_key_with(from, to, rng, &mut Scratch::new())
+    }
+
+    pub fn key_switching_key_with<M: Modulus, R: Rng + ?Sized>(
+        &self,
+        from: &SecretKey,
+        to: &SecretKey,
+        rng: &mut R,
+        scratch: &mut Scratch,
+    ) -> Result<BgvKeySwitchingKey<M>, Error> {
+        self.check_modulus::<M>()?;
+        let from = Zeroizing::new(ModPoly::<M>::reduce(from.power()));
+        let rows = (0..DIGITS)
+            .map(|k| {
+                let a: ModPoly<M> = rng.gen();
+                let factor = ModPoly::from_coeffs(&[(1 << (k * DIGIT_BITS)) % M::VALUE])
+                    .expect("the factor is reduced modulo M");
+                let message = Zeroizing::new(factor * &from);
+                let b = self.encrypt_zero(to.power(), &a, rng) + &message;
+                (
+                    a.lift().into_crt_in_place(scratch),
+                    b.lift().into_crt_in_place(scratch),
+                )
+            })
+            .collect();
+        Ok(BgvKeySwitchingKey {
+            rows,
+            modulus: PhantomData,
+        })
+    }
+
+    pub fn relinearization_key<M: Modulus, R: Rng + ?Sized>(
+        &self,
+        key: &SecretKey,
+        rng: &mut R,
+    ) -> Result<BgvKeySwitchingKey<M>, Error> {
+        self.key_switching_key(&key.squared(), key, rng)
+    }
+
+    pub fn key_switch<M: Modulus>(
+        &self,
+        ciphertext: &BgvCiphertext<M>,
+        key: &BgvKeySwitchingKey<M>,
+    ) -> BgvCiphertext<M> {
+        self.key_switch_with(ciphertext, key, &mut Scratch::new())
+    }
+
+    pub fn key_switch_with<M: Modulus>(
+        &self,
+        ciphertext: &BgvCiphertext<M>,
+        key: &BgvKeySwitchingKey<M>,
+        scratch: &mut Scratch,
+    ) -> BgvCiphertext<M> {
+        let mask = (1 << DIGIT_BITS) - 1;
+        let digits: Vec<ChremPoly> = (0..DIGITS)
+            .map(|k| {
+                let mut digit = PowerPoly::zero();
+                for (residue, &coeff) in digit.iter_mut().zip(ciphertext.a.coeffs()) {
+                    *residue = Residue::from_u64((coeff >> (k * DIGIT_BITS)) & mask);
+                }
+                digit.into_crt_in_place(scratch)
+            })
+            .collect();
+        let a = ChremPoly::inner_product(digits.iter().zip(key.rows.iter().map(|(a, _)| a)));
+        let b = ChremPoly::inner_product(digits.iter().zip(key.rows.iter().map(|(_, b)| b)));
+        BgvCiphertext {
+            a: -ModPoly::reduce(&a.into_power_in_place(scratch)),
+            b: ciphertext.b.clone() - &ModPoly::reduce(&b.into_power_in_place(scratch)),
+        }
+    }
+
+    // Moves the ciphertext to the next modulus of the chain, scaling the noise by M2 / M. Both
+    // moduli must be congruent modulo t, so that the message is preserved.
+    pub fn mod_switch<M: Modulus, M2: Modulus>(
+        &self,
+        ciphertext: &BgvCiphertext<M>,
+    ) -> Result<BgvCiphertext<M2>, Error> {
+        self.check_modulus::<M2>()?;
+        if M::VALUE % self.plaintext_modulus != M2::VALUE % self.plaintext_modulus {
+            return Err(Error::ParameterMismatch(
+                "the moduli of the chain must be congruent modulo the plaintext modulus",
+            ));
+        }
+        Ok(BgvCiphertext {
+            a: self.switch_poly(&ciphertext.a),
+            b: self.switch_poly(&ciphertext.b),
+        })
+    }
+
+    fn check_modulus<M: Modulus>(&self) -> Result<(), Error> {
+        if M::VALUE <= self.plaintext_modulus {
+            return Err(Error::ParameterMismatch(
+                "the ciphertext modulus must exceed the plaintext modulus",
+            ));
+        }
+        Ok(())
+    }
+
+    // a s + t e for a fresh error e
+    fn encrypt_zero<M: Modulus, R: Rng + ?Sized>(
+        &self,
+        key: &PowerPoly,
+        a: &ModPoly<M>,
+        rng: &mut R,
+    ) -> ModPoly<M> {
+        let error = sample_error_poly(rng);
+        let error = Zeroizing::new((*error).clone() * Residue::from_u64(self.plaintext_modulus));
+        let key = Zeroizing::new(ModPoly::<M>::reduce(key));
+        let a_s = Zeroizing::new(a.clone() * &key);
+        ModPoly::reduce(&error) + &a_s
+    }
+
+    // The closest integers to M2 / M times the centered coefficients which are congruent to them
+    // modulo t
+    fn switch_poly<M: Modulus, M2: Modulus>(&self, poly: &ModPoly<M>) -> ModPoly<M2> {
+        let t = self.plaintext_modulus as i128;
+        let scaled = poly.mod_switch::<M2>();
+        let coeffs: Vec<u64> = poly
+            .centered()
+            .zip(scaled.centered())
+            .map(|(x, y)| {
+                // The correction is congruent to x - y modulo t, with the smallest magnitude.
+                let mut correction = (x as i128 - y as i128).rem_euclid(t);
+                if correction > t / 2 {
+                    correction -= t;
+                }
+                (y as i128 + correction).rem_euclid(M2::VALUE as i128) as u64
+            })
+            .collect();
+        ModPoly::from_coeffs(&coeffs).expect("the coefficients are reduced modulo M2")
+    }
+}
@@ -0,0 +1,334 @@
+use crate::error::Error;
+use crate::modulus::*;
+use crate::poly::*;
+use crate::residue::*;
+use crate::rlwe::*;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+use core::iter::successors;
+use core::ops::{Add, Mul, Sub};
+use rand::Rng;
+use zeroize::Zeroizing;
+
+// CKKS encoding: a vector of SLOTS complex numbers is mapped to the polynomial whose canonical
+// embedding, i.e. its evaluations at the roots ZETA^(5^j) for ZETA = exp(PI i / DEGREE), is the
+// vector scaled by the given factor, rounded to integer coefficients. The remaining evaluations are
+// the complex conjugates, so the coefficients are real.
+//
+// Encodings are encrypted modulo a level of a chain of word-sized moduli, as in BGV but without a
+// plaintext modulus: the phase is the encoding plus a small error, which only costs precision.
+// Ciphertexts can be added and multiplied by plaintext encodings, which multiplies the scales.
+// Rescaling switches to the next modulus of the chain, dividing the phase and the scale by M / M2.
+// Products of two ciphertexts, which need relinearization, aren't provided.
+
+pub const SLOTS: usize = DEGREE / 2;
+const INDEX: usize = 2 * DEGREE;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+// An encoding together with its scale
+#[derive(Clone, Debug, PartialEq)]
+pub struct Encoded {
+    pub poly: PowerPoly,
+    pub scale: f64,
+}
+
+// Ciphertext modulo M with phase b - a * s, which approximates an encoding with the given scale
+pub struct CkksCiphertext<M> {
+    pub a: ModPoly<M>,
+    pub b: ModPoly<M>,
+    pub scale: f64,
+}
+
+impl<M> Clone for CkksCiphertext<M> {
+    fn clone(&self) -> Self {
+        CkksCiphertext {
+            a: self.a.clone(),
+            b: self.b.clone(),
+            scale: self.scale,
+        }
+    }
+}
+
+impl<M: Modulus> CkksCiphertext<M> {
+    // Fails if a coefficient of the encoding exceeds M / 2 in magnitude.
+    pub fn encrypt<R: Rng + ?Sized>(
+        key: &SecretKey,
+        encoded: &Encoded,
+        rng: &mut R,
+    ) -> Result<CkksCiphertext<M>, Error> {
+        let message = reduce_encoding::<M>(&encoded.poly)?;
+        let a: ModPoly<M> = rng.gen();
+        let key = Zeroizing::new(ModPoly::<M>::reduce(key.power()));
+        let a_s = Zeroizing::new(a.clone() * &key);
+        let error = Zeroizing::new(ModPoly::<M>::reduce(&sample_error_poly(rng)));
+        let b = message + &a_s + &error;
+        Ok(CkksCiphertext {
+            a,
+            b,
+            scale: encoded.scale,
+        })
+    }
+
+    // The phase, lifted to centered coefficients
+    pub fn decrypt(&self, key: &SecretKey) -> Encoded {
+        let key = Zeroizing::new(ModPoly::<M>::reduce(key.power()));
+        let a_s = Zeroizing::new(self.a.clone() * &key);
+        let phase = Zeroizing::new(self.b.clone() - &a_s);
+        Encoded {
+            poly: phase.lift(),
+            scale: self.scale,
+        }
+    }
+
+    // Fails if the scales differ.
+    pub fn add(&self, other: &CkksCiphertext<M>) -> Result<CkksCiphertext<M>, Error> {
+        if self.scale != other.scale {
+            return Err(Error::ParameterMismatch("the scales differ"));
+        }
+        Ok(CkksCiphertext {
+            a: self.a.clone() + &other.a,
+            b: self.b.clone() + &other.b,
+            scale: self.scale,
+        })
+    }
+
+    // Multiplies the phase by the encoding. The coefficients of the product must stay below M / 2
+    // in magnitude, which is up to the caller.
+    pub fn mul_plain(&self, encoded: &Encoded) -> Result<CkksCiphertext<M>, Error> {
+        let plaintext = reduce_encoding::<M>(&encoded.poly)?;
+        Ok(CkksCiphertext {
+            a: self.a.clone() * &plaintext,
+            b: self.b.clone() * &plaintext,
+            scale: self.scale * encoded.scale,
+        })
+    }
+
+    // Switches to the smaller modulus M2, which divides the phase and the scale by M / M2. The
+    // rounding adds an error of about the size of a * s / M2, i.e. of the key's norm.
+    pub fn rescale<M2: Modulus>(&self) -> Result<CkksCiphertext<M2>, Error> {
+        if M2::VALUE >= M::VALUE {
+            return Err(Error::ParameterMismatch(
+                "rescaling must switch to a smaller modulus",
+            ));
+        }
+        Ok(CkksCiphertext {
+            a: self.a.mod_switch(),
+            b: self.b.mod_switch(),
+            scale: self.scale * M2::VALUE as f64 / M::VALUE as f64,
+        })
+    }
+}
+
+// Reduces the coefficients modulo M, failing if one exceeds M / 2 in magnitude, as it would wrap.
+fn reduce_encoding<M: Modulus>(poly: &PowerPoly) -> Result<ModPoly<M>, Error> {
+    let bound = U384::from(M::VALUE / 2);
+    if poly
+        .iter()
+        .any(|coeff| coeff.to_centered().magnitude > bound)
+    {
+        return Err(Error::OutOfRange);
+    }
+    Ok(ModPoly::reduce(poly))
+}
+
+pub struct CkksEncoder {
+    // ZETA^k for k in [INDEX + 1]
+    roots: Vec<Complex>,
+    // 5^j mod INDEX for j in [SLOTS]
+    rotation_group: Vec<usize>,
+}
+
+impl CkksEncoder {
+    pub fn new() -> CkksEncoder {
+        let roots = (0..=INDEX)
+            .map(|k| {
+                let angle = 2.0 * PI * k as f64 / INDEX as f64;
+                Complex::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        let rotation_group = successors(Some(1), |&power| Some(power * 5 % INDEX))
+            .take(SLOTS)
+            .collect();
+        CkksEncoder {
+            roots,
+            rotation_group,
+        }
+    }
+
+    // Pads the values with zeroes. Fails if a scaled coefficient rounds to 2^382 or more in
+    // magnitude.
+    pub fn encode(&self, values: &[Complex], scale: f64) -> Result<Encoded, Error> {
+        if values.len() > SLOTS {
+            return Err(Error::LengthMismatch {
+                expected: SLOTS,
+                actual: values.len(),
+            });
+        }
+        let mut slots = vec![Complex::default(); SLOTS];
+        slots[..values.len()].copy_from_slice(values);
+        self.special_inverse_fft(&mut slots);
+        let mut poly = PowerPoly::zero();
+        for (i, value) in slots.iter().enumerate() {
+            poly[i] = residue_from_f64(value.re * scale)?;
+            poly[i + SLOTS] = residue_from_f64(value.im * scale)?;
+        }
+        Ok(Encoded { poly, scale })
+    }
+
+    pub fn decode(&self, encoded: &Encoded) -> Vec<Complex> {
+        let mut slots: Vec<Complex> = (0..SLOTS)
+            .map(|i| {
+                Complex::new(
+                    encoded.poly[i].to_centered().to_f64() / encoded.scale,
+                    encoded.poly[i + SLOTS].to_centered().to_f64() / encoded.scale,
+                )
+            })
+            .collect();
+        self.special_fft(&mut slots);
+        slots
+    }
+
+    // Divides the coefficients and the scale by the divisor, rounding the centered coefficients to
+    // the nearest integer. This brings the scale of a product of plaintext encodings back down.
+    pub fn rescale(&self, encoded: &Encoded, divisor: u64) -> Result<Encoded, Error> {
+        if divisor == 0 {
+            return Err(Error::ParameterMismatch("divisor is zero"));
+        }
+        let divisor_big = U384::from(divisor);
+        let mut poly = PowerPoly::zero();
+        for (residue, coeff) in poly.iter_mut().zip(encoded.poly.iter()) {
+            let centered = coeff.to_centered();
+            *residue = Residue::from(Centered {
+                negative: centered.negative,
+                magnitude: (centered.magnitude + (divisor_big >> 1)) / divisor_big,
+            });
+        }
+        Ok(Encoded {
+            poly,
+            scale: encoded.scale / divisor as f64,
+        })
+    }
+
+    // Evaluates the polynomial with coefficients re + i * im at the roots ZETA^(5^j), where the
+    // input holds re in the real and im in the imaginary parts, in bit-reversed butterflies.
+    fn special_fft(&self, values: &mut [Complex]) {
+        bit_reverse(values);
+        let mut len = 2;
+        while len <= values.len() {
+            let half = len / 2;
+            let quarter_index = 4 * len;
+            for chunk in values.chunks_exact_mut(len) {
+                for j in 0..half {
+                    let exponent = self.rotation_group[j] % quarter_index * INDEX / quarter_index;
+                    let u = chunk[j];
+                    let v = chunk[j + half] * self.roots[exponent];
+                    chunk[j] = u + v;
+                    chunk[j + half] = u - v;
+                }
+            }
+            len *= 2;
+        }
+    }
+
+    fn special_inverse_fft(&self, values: &mut [Complex]) {
+        let mut len = values.len();
+        while len >= 2 {
+            let half = len / 2;
+            let quarter_index = 4 * len;
+            for chunk in values.chunks_exact_mut(len) {
+                for j in 0..half {
+                    let exponent = (quarter_index - self.rotation_group[j] % quarter_index) * INDEX
+                        / quarter_index;
+                    let u = chunk[j] + chunk[j + half];
+                    let v = (chunk[j] - chunk[j + half]) * self.roots[exponent];
+                    chunk[j] = u;
+                    chunk[j + half] = v;
+                }
+            }
+            len /= 2;
+        }
+        bit_reverse(values);
+        let size = values.len() as f64;
+        for value in values.iter_mut() {
+            *value = Complex::new(value.re / size, value.im / size);
+        }
+    }
+}
+
+impl Default for CkksEncoder {
+    fn default() -> CkksEncoder {
+        CkksEncoder::new()
+    }
+}
+
+fn bit_reverse(values: &mut [Complex]) {
+    let bits = values.len().trailing_zeros();
+    for i in 0..values.len() {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+// Rounds to the nearest integer, which must be below 2^382 in magnitude.
+fn residue_from_f64(x: f64) -> Result<Residue, Error> {
+    let x = x.round();
+    if !x.is_finite() || x.abs() >= 2f64.powi(382) {
+        return Err(Error::OutOfRange);
+    }
+    if x.abs() < 2f64.powi(63) {
+        return Ok(Residue::from_i64(x as i64));
+    }
+    // x = mantissa * 2^exponent with a 53-bit mantissa and an exponent of at least 11
+    let bits = x.to_bits();
+    let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+    let exponent = ((bits >> 52) & 0x7ff) as usize - 1075;
+    Ok(Residue::from(Centered {
+        negative: x < 0.0,
+        magnitude: U384::from(mantissa) << exponent,
+    }))
+}
@@ -0,0 +1,29 @@
+use core::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    // A slice does not have the expected length, or is too short.
+    LengthMismatch { expected: usize, actual: usize },
+    // A value is not below MODULUS, or does not fit the target type.
+    OutOfRange,
+    // The parameters are inconsistent with each other or with the ring.
+    ParameterMismatch(&'static str),
+    // Malformed byte encoding
+    Decode(&'static str),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::LengthMismatch { expected, actual } => {
+                write!(f, "expected length {}, got {}", expected, actual)
+            }
+            Error::OutOfRange => write!(f, "value out of range"),
+            Error::ParameterMismatch(reason) => write!(f, "parameter mismatch: {}", reason),
+            Error::Decode(reason) => write!(f, "decode error: {}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
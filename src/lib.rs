@@ -0,0 +1,46 @@
+// Arithmetic in Z_q[X]/(X^DEGREE + 1) for the prime q = MODULUS, in the power basis (PowerPoly)
+// and the CRT representation (ChremPoly), together with the RLWE primitives built on top of it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[macro_use]
+extern crate uint;
+
+pub mod batch;
+pub mod bfv;
+pub mod bgv;
+// The floating-point math of the CKKS encoding needs std.
+#[cfg(feature = "std")]
+pub mod ckks;
+pub mod error;
+pub mod modulus;
+pub mod noise;
+pub mod packing;
+mod parallel;
+pub mod poly;
+pub mod residue;
+pub mod rgsw;
+pub mod rlwe;
+mod simd;
+#[cfg(all(test, feature = "std"))]
+mod test;
+// The noise estimates are a debugging aid, so they are opt-in.
+#[cfg(feature = "noise-tracking")]
+pub mod tracked;
+pub mod transpose;
+
+pub use crate::batch::BatchEncoder;
+pub use crate::bfv::{Bfv, RelinearizationKey};
+pub use crate::bgv::{Bgv, BgvCiphertext, BgvKeySwitchingKey};
+#[cfg(feature = "std")]
+pub use crate::ckks::{CkksCiphertext, CkksEncoder, Complex, Encoded};
+pub use crate::error::Error;
+pub use crate::modulus::{ModPoly, Modulus};
+pub use crate::noise::{decrypt_with_noise, NoiseInfo};
+pub use crate::poly::{ChremPoly, DecodingPoly, EvalPoly, PowerPoly, PowerfulPoly, Scratch};
+pub use crate::residue::{Accumulator, Centered, Residue, DEGREE, MODULUS};
+pub use crate::rgsw::RgswCiphertext;
+pub use crate::rlwe::{KeySwitchingKey, LweCiphertext, Plaintext, RlweCiphertext, SecretKey};
+#[cfg(feature = "noise-tracking")]
+pub use crate::tracked::Tracked;
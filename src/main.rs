@@ -1,15 +1,8 @@
-#[macro_use]
-extern crate uint;
-
-mod poly;
-mod residue;
-mod test;
-
-use crate::residue::*;
+use rgsw::Residue;
 
 fn main() {
     let one = Residue::one();
-    let big = 2 as u64 * one;
+    let big = 2u64 * one;
     let big = big * big;
     let big = big * big;
     let big = big * big;
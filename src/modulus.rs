@@ -0,0 +1,253 @@
+use crate::error::Error;
+use crate::poly::*;
+use crate::residue::*;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+use zeroize::Zeroize;
+
+// A word-sized modulus other than MODULUS, as a type so that polynomials over different moduli
+// can't be mixed up. VALUE must be at least 2 and below 2^63.
+pub trait Modulus {
+    const VALUE: u64;
+
+    // Evaluated for every M that polynomials or batch encoders are created for, so that an
+    // out-of-range VALUE fails to compile. Not meant to be overridden.
+    #[doc(hidden)]
+    const CHECK: () = assert!(
+        Self::VALUE >= 2 && Self::VALUE < 1 << 63,
+        "Modulus::VALUE must be at least 2 and below 2^63"
+    );
+}
+
+// Polynomial in Z_M[X]/(X^DEGREE + 1) for a word-sized modulus M, in the power basis. The
+// coefficients are the canonical representatives.
+pub struct ModPoly<M> {
+    coeffs: Vec<u64>,
+    modulus: PhantomData<M>,
+}
+
+impl<M: Modulus> ModPoly<M> {
+    // All polynomials are created here, which checks M at compile time.
+    fn new(coeffs: Vec<u64>) -> ModPoly<M> {
+        let () = M::CHECK;
+        ModPoly {
+            coeffs,
+            modulus: PhantomData,
+        }
+    }
+
+    pub fn zero() -> ModPoly<M> {
+        ModPoly::new(vec![0; DEGREE])
+    }
+
+    // Pads the coefficients with zeroes.
+    pub fn from_coeffs(coeffs: &[u64]) -> Result<ModPoly<M>, Error> {
+        if coeffs.len() > DEGREE {
+            return Err(Error::LengthMismatch {
+                expected: DEGREE,
+                actual: coeffs.len(),
+            });
+        }
+        if coeffs.iter().any(|&coeff| coeff >= M::VALUE) {
+            return Err(Error::OutOfRange);
+        }
+        let mut result = ModPoly::zero();
+        result.coeffs[..coeffs.len()].copy_from_slice(coeffs);
+        Ok(result)
+    }
+
+    pub fn coeffs(&self) -> &[u64] {
+        &self.coeffs
+    }
+
+    // The coefficients' representatives in (-M / 2, M / 2]
+    pub fn centered(&self) -> impl Iterator<Item = i64> + '_ {
+        self.coeffs.iter().map(|&coeff| {
+            if coeff > M::VALUE / 2 {
+                -((M::VALUE - coeff) as i64)
+            } else {
+                coeff as i64
+            }
+        })
+    }
+
+    // Reduces the centered coefficients modulo M.
+    pub fn reduce(poly: &PowerPoly) -> ModPoly<M> {
+        let modulus = U384::from(M::VALUE);
+        let coeffs = poly
+            .iter()
+            .map(|coeff| {
+                let centered = coeff.to_centered();
+                let reduced = (centered.magnitude % modulus).low_u64();
+                if centered.negative && reduced != 0 {
+                    M::VALUE - reduced
+                } else {
+                    reduced
+                }
+            })
+            .collect();
+        ModPoly::new(coeffs)
+    }
+
+    // The centered coefficients as residues modulo MODULUS. Products of DEGREE such coefficients
+    // stay far below MODULUS / 2, so ring products of lifts are exact.
+    pub fn lift(&self) -> PowerPoly {
+        let mut result = PowerPoly::zero();
+        for (residue, coeff) in result.iter_mut().zip(self.centered()) {
+            *residue = Residue::from_i64(coeff);
+        }
+        result
+    }
+
+    pub fn linf_norm(&self) -> u64 {
+        self.centered()
+            .map(|coeff| coeff.unsigned_abs())
+            .max()
+            .unwrap_or_default()
+    }
+
+    // Scales by M2 / M, rounding the centered coefficients to the nearest integer.
+    pub fn mod_switch<M2: Modulus>(&self) -> ModPoly<M2> {
+        let from = U448::from(M::VALUE);
+        let coeffs = self
+            .centered()
+            .map(|coeff| {
+                let magnitude = U448::from(coeff.unsigned_abs());
+                switch_coeff::<M2>(coeff < 0, magnitude, from)
+            })
+            .collect();
+        ModPoly::new(coeffs)
+    }
+}
+
+impl PowerPoly {
+    // Scales by M2 / MODULUS, rounding the centered coefficients to the nearest integer.
+    pub fn mod_switch<M2: Modulus>(&self) -> ModPoly<M2> {
+        let from = U448::from(MODULUS);
+        let coeffs = self
+            .iter()
+            .map(|coeff| {
+                let centered = coeff.to_centered();
+                switch_coeff::<M2>(centered.negative, U448::from(centered.magnitude), from)
+            })
+            .collect();
+        ModPoly::new(coeffs)
+    }
+}
+
+// round(magnitude * M2 / from) with the given sign, modulo M2. The magnitude is at most from / 2
+// and from is below 2^384, so nothing overflows.
+fn switch_coeff<M2: Modulus>(negative: bool, magnitude: U448, from: U448) -> u64 {
+    let rounded = (magnitude * U448::from(M2::VALUE) + (from >> 1)) / from;
+    let rounded = (rounded % U448::from(M2::VALUE)).low_u64();
+    if negative && rounded != 0 {
+        M2::VALUE - rounded
+    } else {
+        rounded
+    }
+}
+
+impl<M> Clone for ModPoly<M> {
+    fn clone(&self) -> Self {
+        ModPoly {
+            coeffs: self.coeffs.clone(),
+            modulus: PhantomData,
+        }
+    }
+}
+
+impl<M> PartialEq for ModPoly<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.coeffs == other.coeffs
+    }
+}
+
+impl<M> Debug for ModPoly<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.coeffs.iter()).finish()
+    }
+}
+
+impl<M> Zeroize for ModPoly<M> {
+    fn zeroize(&mut self) {
+        self.coeffs.as_mut_slice().zeroize();
+    }
+}
+
+impl<M: Modulus> Distribution<ModPoly<M>> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ModPoly<M> {
+        ModPoly::new((0..DEGREE).map(|_| rng.gen_range(0, M::VALUE)).collect())
+    }
+}
+
+impl<M: Modulus> Neg for ModPoly<M> {
+    type Output = ModPoly<M>;
+
+    fn neg(mut self) -> ModPoly<M> {
+        for a in self.coeffs.iter_mut() {
+            if *a != 0 {
+                *a = M::VALUE - *a;
+            }
+        }
+        self
+    }
+}
+
+impl<M: Modulus> AddAssign<&ModPoly<M>> for ModPoly<M> {
+    fn add_assign(&mut self, other: &ModPoly<M>) {
+        for (a, &b) in self.coeffs.iter_mut().zip(other.coeffs.iter()) {
+            // Both are below 2^63, so the sum doesn't overflow.
+            *a += b;
+            if *a >= M::VALUE {
+                *a -= M::VALUE;
+            }
+        }
+    }
+}
+
+impl<M: Modulus> Add<&ModPoly<M>> for ModPoly<M> {
+    type Output = ModPoly<M>;
+
+    fn add(mut self, other: &ModPoly<M>) -> ModPoly<M> {
+        self += other;
+        self
+    }
+}
+
+impl<M: Modulus> SubAssign<&ModPoly<M>> for ModPoly<M> {
+    fn sub_assign(&mut self, other: &ModPoly<M>) {
+        for (a, &b) in self.coeffs.iter_mut().zip(other.coeffs.iter()) {
+            *a = if *a >= b { *a - b } else { *a + M::VALUE - b };
+        }
+    }
+}
+
+impl<M: Modulus> Sub<&ModPoly<M>> for ModPoly<M> {
+    type Output = ModPoly<M>;
+
+    fn sub(mut self, other: &ModPoly<M>) -> ModPoly<M> {
+        self -= other;
+        self
+    }
+}
+
+impl<M: Modulus> MulAssign<&ModPoly<M>> for ModPoly<M> {
+    fn mul_assign(&mut self, other: &ModPoly<M>) {
+        let product = ChremPoly::from(self.lift()) * &ChremPoly::from(other.lift());
+        *self = ModPoly::reduce(&PowerPoly::from(product));
+    }
+}
+
+impl<M: Modulus> Mul<&ModPoly<M>> for ModPoly<M> {
+    type Output = ModPoly<M>;
+
+    fn mul(mut self, other: &ModPoly<M>) -> ModPoly<M> {
+        self *= other;
+        self
+    }
+}
@@ -0,0 +1,53 @@
+use crate::error::Error;
+use crate::poly::*;
+use crate::residue::*;
+use crate::rlwe::*;
+
+// Infinity norm of the noise in centered representation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseInfo {
+    pub linf_norm: U384,
+}
+
+impl NoiseInfo {
+    pub fn measure(noise: &PowerPoly) -> NoiseInfo {
+        NoiseInfo {
+            linf_norm: noise.linf_norm(),
+        }
+    }
+
+    // floor(log2(linf_norm)) + 1, or 0 without noise
+    pub fn bits(&self) -> u32 {
+        self.linf_norm.bits() as u32
+    }
+
+    #[cfg(feature = "std")]
+    pub fn log2(&self) -> f64 {
+        let magnitude = Centered {
+            negative: false,
+            magnitude: self.linf_norm,
+        };
+        magnitude.to_f64().log2()
+    }
+
+    // Bits of noise that can still be added before decryption with the plaintext modulus fails,
+    // i.e. log2(scale / 2) - log2(linf_norm) rounded to bit lengths
+    pub fn budget(&self, plaintext_modulus: u64) -> i64 {
+        let half_scale = Plaintext::scale(plaintext_modulus).0 >> 1;
+        half_scale.bits() as i64 - self.bits() as i64
+    }
+}
+
+// Decrypts with the given plaintext modulus, measuring the distance of the phase from the encoding
+// of the plaintext. Ciphertexts don't record their plaintext modulus, so it has to be passed like
+// for Plaintext::decode, which also makes this fallible.
+pub fn decrypt_with_noise(
+    key: &SecretKey,
+    ciphertext: &RlweCiphertext,
+    plaintext_modulus: u64,
+) -> Result<(Plaintext, NoiseInfo), Error> {
+    let phase = ciphertext.phase(key);
+    let plaintext = Plaintext::decode(&phase, plaintext_modulus)?;
+    let noise = NoiseInfo::measure(&(phase - &plaintext.encode()));
+    Ok((plaintext, noise))
+}
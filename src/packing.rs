@@ -0,0 +1,121 @@
+use crate::error::Error;
+use crate::poly::*;
+use crate::residue::*;
+use crate::rlwe::*;
+use alloc::vec::Vec;
+use rand::Rng;
+
+// log_2(DEGREE), the number of automorphisms needed for the trace
+const LOG_DEGREE: u32 = DEGREE.trailing_zeros();
+
+// Key switching key for the automorphism X -> X^exponent
+pub struct AutomorphismKey {
+    exponent: usize,
+    key: KeySwitchingKey,
+}
+
+impl AutomorphismKey {
+    pub fn generate<R: Rng + ?Sized>(
+        key: &SecretKey,
+        exponent: usize,
+        rng: &mut R,
+    ) -> AutomorphismKey {
+        AutomorphismKey {
+            exponent,
+            key: KeySwitchingKey::generate(&key.automorphism(exponent), key, rng),
+        }
+    }
+
+    // Applies the automorphism to the phase, keeping the secret key.
+    pub fn apply(&self, ciphertext: &RlweCiphertext) -> RlweCiphertext {
+        self.key.switch(&ciphertext.automorphism(self.exponent))
+    }
+}
+
+// Automorphism keys for X -> X^(2^k + 1), for k in 1..=log_2(DEGREE)
+pub struct PackingKeys(Vec<AutomorphismKey>);
+
+impl PackingKeys {
+    pub fn generate<R: Rng + ?Sized>(key: &SecretKey, rng: &mut R) -> PackingKeys {
+        PackingKeys(
+            (1..=LOG_DEGREE)
+                .map(|k| AutomorphismKey::generate(key, (1 << k) + 1, rng))
+                .collect(),
+        )
+    }
+
+    fn automorphism_key(&self, k: u32) -> &AutomorphismKey {
+        &self.0[k as usize - 1]
+    }
+
+    // Field trace into the subring generated by X^(DEGREE / 2^log_n). This keeps the
+    // coefficients at multiples of DEGREE / 2^log_n, scaled by 2^(log_2(DEGREE) - log_n),
+    // and zeroes all other coefficients.
+    fn partial_trace(&self, mut ciphertext: RlweCiphertext, log_n: u32) -> RlweCiphertext {
+        for k in (log_n + 1..=LOG_DEGREE).rev() {
+            let image = self.automorphism_key(k).apply(&ciphertext);
+            ciphertext += &image;
+        }
+        ciphertext
+    }
+
+    // Homomorphic trace to Z_q, in log_2(DEGREE) steps. The phase of the result is DEGREE times
+    // the constant coefficient of the input phase.
+    pub fn trace(&self, ciphertext: RlweCiphertext) -> RlweCiphertext {
+        self.partial_trace(ciphertext, 0)
+    }
+
+    // Packs LWE ciphertexts (Chen, Dai, Kim, Song: Efficient Homomorphic Conversion Between
+    // (Ring) LWE Ciphertexts). With n the number of ciphertexts rounded up to a power of two,
+    // the phase of lwes[j] ends up in the coefficient at j * DEGREE / n of the result. All other
+    // coefficients are zero, up to noise.
+    pub fn pack_lwes(&self, lwes: &[LweCiphertext]) -> Result<RlweCiphertext, Error> {
+        if lwes.is_empty() || lwes.len() > DEGREE {
+            return Err(Error::ParameterMismatch(
+                "the number of LWE ciphertexts must be from 1 to DEGREE",
+            ));
+        }
+        let n = lwes.len().next_power_of_two();
+        // Packing and the trace scale the phases by DEGREE in total.
+        let inv_degree = Residue::from_u64(DEGREE as u64).inv();
+        let ciphertexts = (0..n)
+            .map(|j| match lwes.get(j) {
+                Some(lwe) => {
+                    let ciphertext = RlweCiphertext::from(lwe);
+                    RlweCiphertext {
+                        a: ciphertext.a * inv_degree,
+                        b: ciphertext.b * inv_degree,
+                    }
+                }
+                None => RlweCiphertext::trivial(PowerPoly::zero()),
+            })
+            .collect();
+        let packed = self.pack(ciphertexts);
+        Ok(self.partial_trace(packed, n.trailing_zeros()))
+    }
+
+    // Algorithm 2 (PackLWEs) of Chen, Dai, Kim, Song
+    fn pack(&self, mut ciphertexts: Vec<RlweCiphertext>) -> RlweCiphertext {
+        let n = ciphertexts.len();
+        if n == 1 {
+            return ciphertexts.remove(0);
+        }
+        let mut even = Vec::with_capacity(n / 2);
+        let mut odd = Vec::with_capacity(n / 2);
+        for (j, ciphertext) in ciphertexts.into_iter().enumerate() {
+            if j % 2 == 0 {
+                even.push(ciphertext);
+            } else {
+                odd.push(ciphertext);
+            }
+        }
+        let even = self.pack(even);
+        let odd = self.pack(odd).mul_monomial(DEGREE / n);
+        let mut diff = even.clone();
+        diff -= &odd;
+        let mut sum = even;
+        sum += &odd;
+        sum += &self.automorphism_key(n.trailing_zeros()).apply(&diff);
+        sum
+    }
+}
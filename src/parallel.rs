@@ -0,0 +1,113 @@
+// Helpers that split large amounts of work among threads with the "parallel" feature. Without the
+// feature, or with Schedule::Serial, they run everything on the calling thread.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// Smallest amount of work that is split among threads
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 4096;
+
+// Number of elements processed per task
+pub(crate) const PARALLEL_CHUNK: usize = 1024;
+
+// Whether the helpers may split work among threads. Only the tests ask for the serial path, to
+// compare the parallel one against it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Schedule {
+    #[cfg_attr(not(all(test, feature = "parallel")), allow(dead_code))]
+    Serial,
+    Parallel,
+}
+
+impl Schedule {
+    // Whether work on len elements is split among threads
+    #[cfg(feature = "parallel")]
+    fn splits(self, len: usize) -> bool {
+        self == Schedule::Parallel && len >= PARALLEL_THRESHOLD
+    }
+}
+
+// Runs both closures, in parallel if the work on len elements is split.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+pub(crate) fn join<A, B>(schedule: Schedule, len: usize, a: A, b: B)
+where
+    A: FnOnce() + Send,
+    B: FnOnce() + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        if schedule.splits(len) {
+            rayon::join(a, b);
+            return;
+        }
+    }
+    a();
+    b();
+}
+
+// Calls f(offset, chunk) for consecutive chunks of data.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+pub(crate) fn for_each_chunk<T, F>(schedule: Schedule, data: &mut [T], chunk_size: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        if schedule.splits(data.len()) {
+            data.par_chunks_mut(chunk_size)
+                .enumerate()
+                .for_each(|(i, chunk)| f(i * chunk_size, chunk));
+            return;
+        }
+    }
+    for (i, chunk) in data.chunks_mut(chunk_size).enumerate() {
+        f(i * chunk_size, chunk);
+    }
+}
+
+// Calls f on corresponding chunks of a and b.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+pub(crate) fn for_each_chunk_pair<T, F>(
+    schedule: Schedule,
+    a: &mut [T],
+    b: &mut [T],
+    chunk_size: usize,
+    f: F,
+) where
+    T: Send,
+    F: Fn(&mut [T], &mut [T]) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        if schedule.splits(a.len()) {
+            a.par_chunks_mut(chunk_size)
+                .zip(b.par_chunks_mut(chunk_size))
+                .for_each(|(a, b)| f(a, b));
+            return;
+        }
+    }
+    for (a, b) in a.chunks_mut(chunk_size).zip(b.chunks_mut(chunk_size)) {
+        f(a, b);
+    }
+}
+
+// Applies f to corresponding chunks of a and b, or to all of them at once if the work isn't split.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+pub(crate) fn zip_apply<T, F>(schedule: Schedule, a: &mut [T], b: &[T], f: F)
+where
+    T: Send + Sync,
+    F: Fn(&mut [T], &[T]) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        if schedule.splits(a.len()) {
+            a.par_chunks_mut(PARALLEL_CHUNK)
+                .zip(b.par_chunks(PARALLEL_CHUNK))
+                .for_each(|(a, b)| f(a, b));
+            return;
+        }
+    }
+    f(a, b);
+}
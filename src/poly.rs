@@ -1,13 +1,115 @@
+use crate::error::Error;
+use crate::parallel::*;
 use crate::residue::*;
-use std::fmt::{Debug, Formatter};
-use std::ops::{AddAssign, MulAssign, Neg, SubAssign};
+use crate::simd;
+use crate::transpose::transpose;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::iter::{successors, Product, Sum};
+use core::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+use core::slice::{Iter, IterMut};
+use once_cell::race::OnceBox;
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+use zeroize::Zeroize;
 
-trait EvalPoly {
-    fn eval(self, x: Residue) -> Residue;
+pub trait EvalPoly {
+    fn eval(&self, x: Residue) -> Residue;
+
+    fn eval_many(&self, xs: &[Residue]) -> Vec<Residue> {
+        xs.iter().map(|&x| self.eval(x)).collect()
+    }
 }
 
+// CRT representation: slot i holds the evaluation at INDEX_TH_ROOT^(2i + 1), i.e. the slots are
+// the evaluations at the primitive INDEX_BASE^INDEX_POWER-th roots of unity in ascending order
+// of their exponents.
 #[derive(Clone)]
-pub struct ChremPoly(pub Box<[Residue; DEGREE]>);
+pub struct ChremPoly(pub(crate) Box<[Residue; DEGREE]>);
+
+impl ChremPoly {
+    // The root of unity at which the polynomial is evaluated in slot `index`
+    pub fn slot_point(index: usize) -> Result<Residue, Error> {
+        if index >= DEGREE {
+            return Err(Error::OutOfRange);
+        }
+        Ok(INDEX_TH_ROOT.pow(2 * index as u32 + 1))
+    }
+
+    // The roots of unity of all slots, in slot order
+    pub fn slot_points() -> Vec<Residue> {
+        let step = INDEX_TH_ROOT * INDEX_TH_ROOT;
+        successors(Some(INDEX_TH_ROOT), |&point| Some(point * step))
+            .take(DEGREE)
+            .collect()
+    }
+
+    pub fn zero() -> ChremPoly {
+        ChremPoly(boxed_residues(Residue::zero()))
+    }
+
+    pub fn one() -> ChremPoly {
+        ChremPoly(boxed_residues(Residue::one()))
+    }
+
+    pub fn from_slots(slots: &[Residue]) -> Result<ChremPoly, Error> {
+        if slots.len() != DEGREE {
+            return Err(Error::LengthMismatch {
+                expected: DEGREE,
+                actual: slots.len(),
+            });
+        }
+        let mut result = ChremPoly::zero();
+        result.0.clone_from_slice(slots);
+        Ok(result)
+    }
+
+    // The CRT representation of X^exponent
+    pub fn monomial(exponent: usize) -> ChremPoly {
+        let exponent = exponent % (2 * DEGREE);
+        let step = INDEX_TH_ROOT.pow(2 * exponent as u32);
+        let mut result = ChremPoly::zero();
+        let mut slot = INDEX_TH_ROOT.pow(exponent as u32);
+        for residue in result.0.iter_mut() {
+            *residue = slot;
+            slot *= step;
+        }
+        result
+    }
+
+    // Sum of the slot-wise products of the pairs, with a single reduction per slot
+    pub fn inner_product<'a, I>(pairs: I) -> ChremPoly
+    where
+        I: IntoIterator<Item = (&'a ChremPoly, &'a ChremPoly)>,
+    {
+        let pairs: Vec<_> = pairs.into_iter().collect();
+        let mut result = ChremPoly::zero();
+        for_each_chunk(
+            Schedule::Parallel,
+            result.0.as_mut(),
+            PARALLEL_CHUNK,
+            |offset, slots| {
+                for (i, slot) in slots.iter_mut().enumerate() {
+                    let mut sum = Accumulator::zero();
+                    for (a, b) in pairs.iter() {
+                        sum.mul_acc(a[offset + i], b[offset + i]);
+                    }
+                    *slot = sum.reduce();
+                }
+            },
+        );
+        result
+    }
+
+    pub fn iter(&self) -> Iter<'_, Residue> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, Residue> {
+        self.0.iter_mut()
+    }
+}
 
 impl PartialEq for ChremPoly {
     fn eq(&self, other: &Self) -> bool {
@@ -15,6 +117,20 @@ impl PartialEq for ChremPoly {
     }
 }
 
+impl Index<usize> for ChremPoly {
+    type Output = Residue;
+
+    fn index(&self, index: usize) -> &Residue {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for ChremPoly {
+    fn index_mut(&mut self, index: usize) -> &mut Residue {
+        &mut self.0[index]
+    }
+}
+
 impl Neg for ChremPoly {
     type Output = ChremPoly;
 
@@ -28,30 +144,115 @@ impl Neg for ChremPoly {
 
 impl AddAssign<&ChremPoly> for ChremPoly {
     fn add_assign(&mut self, other: &ChremPoly) {
-        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
-            *a += *b;
-        }
+        zip_apply(
+            Schedule::Parallel,
+            self.0.as_mut(),
+            other.0.as_ref(),
+            simd::add_assign,
+        );
+    }
+}
+
+impl Add<&ChremPoly> for ChremPoly {
+    type Output = ChremPoly;
+
+    fn add(mut self, other: &ChremPoly) -> ChremPoly {
+        self += other;
+        self
     }
 }
 
 impl SubAssign<&ChremPoly> for ChremPoly {
     fn sub_assign(&mut self, other: &ChremPoly) {
-        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
-            *a -= *b;
-        }
+        zip_apply(
+            Schedule::Parallel,
+            self.0.as_mut(),
+            other.0.as_ref(),
+            simd::sub_assign,
+        );
+    }
+}
+
+impl Sub<&ChremPoly> for ChremPoly {
+    type Output = ChremPoly;
+
+    fn sub(mut self, other: &ChremPoly) -> ChremPoly {
+        self -= other;
+        self
     }
 }
 
 impl MulAssign<&ChremPoly> for ChremPoly {
     fn mul_assign(&mut self, other: &ChremPoly) {
-        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
-            *a *= *b;
+        zip_apply(
+            Schedule::Parallel,
+            self.0.as_mut(),
+            other.0.as_ref(),
+            simd::mul_assign,
+        );
+    }
+}
+
+impl Mul<&ChremPoly> for ChremPoly {
+    type Output = ChremPoly;
+
+    fn mul(mut self, other: &ChremPoly) -> ChremPoly {
+        self *= other;
+        self
+    }
+}
+
+impl MulAssign<Residue> for ChremPoly {
+    fn mul_assign(&mut self, other: Residue) {
+        for a in self.0.iter_mut() {
+            *a *= other;
         }
     }
 }
 
+impl Mul<Residue> for ChremPoly {
+    type Output = ChremPoly;
+
+    fn mul(mut self, other: Residue) -> ChremPoly {
+        self *= other;
+        self
+    }
+}
+
+impl Mul<ChremPoly> for Residue {
+    type Output = ChremPoly;
+
+    fn mul(self, other: ChremPoly) -> ChremPoly {
+        other * self
+    }
+}
+
+impl Sum for ChremPoly {
+    fn sum<I: Iterator<Item = ChremPoly>>(iter: I) -> ChremPoly {
+        iter.fold(ChremPoly::zero(), |acc, x| acc + &x)
+    }
+}
+
+impl<'a> Sum<&'a ChremPoly> for ChremPoly {
+    fn sum<I: Iterator<Item = &'a ChremPoly>>(iter: I) -> ChremPoly {
+        iter.fold(ChremPoly::zero(), |acc, x| acc + x)
+    }
+}
+
+impl Product for ChremPoly {
+    fn product<I: Iterator<Item = ChremPoly>>(iter: I) -> ChremPoly {
+        iter.fold(ChremPoly::one(), |acc, x| acc * &x)
+    }
+}
+
+impl<'a> Product<&'a ChremPoly> for ChremPoly {
+    fn product<I: Iterator<Item = &'a ChremPoly>>(iter: I) -> ChremPoly {
+        iter.fold(ChremPoly::one(), |acc, x| acc * x)
+    }
+}
+
 impl Debug for ChremPoly {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let mut builder = f.debug_list();
         for residue in self.0.iter() {
             builder.entry(residue);
@@ -62,7 +263,97 @@ impl Debug for ChremPoly {
 }
 
 #[derive(Clone)]
-pub struct PowerPoly(pub Box<[Residue; DEGREE]>);
+pub struct PowerPoly(pub(crate) Box<[Residue; DEGREE]>);
+
+impl PowerPoly {
+    pub fn zero() -> PowerPoly {
+        PowerPoly(boxed_residues(Residue::zero()))
+    }
+
+    pub fn one() -> PowerPoly {
+        PowerPoly::monomial(0)
+    }
+
+    // Pads the coefficients with zeroes. Fails for more than DEGREE coefficients.
+    pub fn from_coeffs(coeffs: &[Residue]) -> Result<PowerPoly, Error> {
+        if coeffs.len() > DEGREE {
+            return Err(Error::LengthMismatch {
+                expected: DEGREE,
+                actual: coeffs.len(),
+            });
+        }
+        let mut result = PowerPoly::zero();
+        result.0[..coeffs.len()].clone_from_slice(coeffs);
+        Ok(result)
+    }
+
+    // X^exponent, which is -X^(exponent - DEGREE) for exponents from DEGREE to 2 * DEGREE.
+    pub fn monomial(exponent: usize) -> PowerPoly {
+        let exponent = exponent % (2 * DEGREE);
+        let mut result = PowerPoly::zero();
+        if exponent < DEGREE {
+            result.0[exponent] = Residue::one();
+        } else {
+            result.0[exponent - DEGREE] = -Residue::one();
+        }
+        result
+    }
+
+    pub fn iter(&self) -> Iter<'_, Residue> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, Residue> {
+        self.0.iter_mut()
+    }
+
+    // Applies the automorphism X -> X^exponent, for odd exponent.
+    pub fn automorphism(&self, exponent: usize) -> PowerPoly {
+        assert!(exponent % 2 == 1);
+        let exponent = exponent % (2 * DEGREE);
+        let mut result = PowerPoly::zero();
+        for (i, coeff) in self.0.iter().enumerate() {
+            let j = i * exponent % (2 * DEGREE);
+            if j < DEGREE {
+                result.0[j] = *coeff;
+            } else {
+                result.0[j - DEGREE] = -*coeff;
+            }
+        }
+        result
+    }
+
+    // Multiplies by X^exponent.
+    pub fn mul_monomial(&self, exponent: usize) -> PowerPoly {
+        let mut result = PowerPoly::zero();
+        for (i, coeff) in self.0.iter().enumerate() {
+            let j = (i + exponent) % (2 * DEGREE);
+            if j < DEGREE {
+                result.0[j] = *coeff;
+            } else {
+                result.0[j - DEGREE] = -*coeff;
+            }
+        }
+        result
+    }
+
+    // Largest absolute value of the centered coefficients
+    pub fn linf_norm(&self) -> U384 {
+        self.0
+            .iter()
+            .map(|coeff| coeff.to_centered().magnitude)
+            .max()
+            .unwrap_or_default()
+    }
+
+    // Sum of the squares of the centered coefficients
+    pub fn l2_norm_squared(&self) -> U832 {
+        self.0.iter().fold(U832::zero(), |sum, coeff| {
+            let magnitude = U768::from(coeff.to_centered().magnitude);
+            sum + U832::from(magnitude * magnitude)
+        })
+    }
+}
 
 impl PartialEq for PowerPoly {
     fn eq(&self, other: &Self) -> bool {
@@ -70,6 +361,20 @@ impl PartialEq for PowerPoly {
     }
 }
 
+impl Index<usize> for PowerPoly {
+    type Output = Residue;
+
+    fn index(&self, index: usize) -> &Residue {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for PowerPoly {
+    fn index_mut(&mut self, index: usize) -> &mut Residue {
+        &mut self.0[index]
+    }
+}
+
 impl Neg for PowerPoly {
     type Output = PowerPoly;
 
@@ -89,6 +394,15 @@ impl AddAssign<&PowerPoly> for PowerPoly {
     }
 }
 
+impl Add<&PowerPoly> for PowerPoly {
+    type Output = PowerPoly;
+
+    fn add(mut self, other: &PowerPoly) -> PowerPoly {
+        self += other;
+        self
+    }
+}
+
 impl SubAssign<&PowerPoly> for PowerPoly {
     fn sub_assign(&mut self, other: &PowerPoly) {
         for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
@@ -97,8 +411,94 @@ impl SubAssign<&PowerPoly> for PowerPoly {
     }
 }
 
+impl Sub<&PowerPoly> for PowerPoly {
+    type Output = PowerPoly;
+
+    fn sub(mut self, other: &PowerPoly) -> PowerPoly {
+        self -= other;
+        self
+    }
+}
+
+// Multiplication in Z_q[X]/(X^DEGREE + 1), via the CRT representation. Only the other factor is
+// copied, and all three conversions share one scratch.
+impl MulAssign<&PowerPoly> for PowerPoly {
+    fn mul_assign(&mut self, other: &PowerPoly) {
+        let mut scratch = Scratch::new();
+        let other = other.clone().into_crt_in_place(&mut scratch);
+        scratch.crt(self.0.as_mut());
+        zip_apply(
+            Schedule::Parallel,
+            self.0.as_mut(),
+            other.0.as_ref(),
+            simd::mul_assign,
+        );
+        scratch.inv_crt(self.0.as_mut());
+    }
+}
+
+impl Mul<&PowerPoly> for PowerPoly {
+    type Output = PowerPoly;
+
+    fn mul(mut self, other: &PowerPoly) -> PowerPoly {
+        self *= other;
+        self
+    }
+}
+
+impl MulAssign<Residue> for PowerPoly {
+    fn mul_assign(&mut self, other: Residue) {
+        for a in self.0.iter_mut() {
+            *a *= other;
+        }
+    }
+}
+
+impl Mul<Residue> for PowerPoly {
+    type Output = PowerPoly;
+
+    fn mul(mut self, other: Residue) -> PowerPoly {
+        self *= other;
+        self
+    }
+}
+
+impl Mul<PowerPoly> for Residue {
+    type Output = PowerPoly;
+
+    fn mul(self, other: PowerPoly) -> PowerPoly {
+        other * self
+    }
+}
+
+impl Sum for PowerPoly {
+    fn sum<I: Iterator<Item = PowerPoly>>(iter: I) -> PowerPoly {
+        iter.fold(PowerPoly::zero(), |acc, x| acc + &x)
+    }
+}
+
+impl<'a> Sum<&'a PowerPoly> for PowerPoly {
+    fn sum<I: Iterator<Item = &'a PowerPoly>>(iter: I) -> PowerPoly {
+        iter.fold(PowerPoly::zero(), |acc, x| acc + x)
+    }
+}
+
+// Multiplies in the CRT representation, converting only once in each direction.
+impl Product for PowerPoly {
+    fn product<I: Iterator<Item = PowerPoly>>(iter: I) -> PowerPoly {
+        PowerPoly::from(iter.map(ChremPoly::from).product::<ChremPoly>())
+    }
+}
+
+impl<'a> Product<&'a PowerPoly> for PowerPoly {
+    fn product<I: Iterator<Item = &'a PowerPoly>>(iter: I) -> PowerPoly {
+        iter.cloned().product()
+    }
+}
+
+// Horner's method
 impl EvalPoly for PowerPoly {
-    fn eval(self, x: Residue) -> Residue {
+    fn eval(&self, x: Residue) -> Residue {
         let mut result = Residue::zero();
         for coeff in self.0.iter().rev() {
             result *= x;
@@ -109,7 +509,7 @@ impl EvalPoly for PowerPoly {
 }
 
 impl Debug for PowerPoly {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let mut builder = f.debug_list();
         for residue in self.0.iter() {
             builder.entry(residue);
@@ -119,267 +519,549 @@ impl Debug for PowerPoly {
     }
 }
 
-fn transpose_impl<T>(
-    majors: usize,
-    minors: usize,
-    src: &[T],
-    dest: &mut [T],
-    my_majors_begin: usize,
-    my_majors_end: usize,
-    my_minors_begin: usize,
-    my_minors_end: usize,
-) where
-    T: Clone,
-{
-    let my_majors = my_majors_end - my_majors_begin;
-    let my_minors = my_minors_end - my_minors_begin;
-    if my_majors * my_minors <= 256 {
-        for maj in my_majors_begin..my_majors_end {
-            for (d, s) in dest
-                .iter_mut()
-                .skip(my_minors_begin * majors + maj)
-                .step_by(majors)
-                .take(my_minors)
-                .zip(
-                    src.iter()
-                        .skip(maj * minors + my_minors_begin)
-                        .take(my_minors),
-                )
-            {
-                *d = s.clone();
-            }
+// Coefficients w.r.t. the powerful basis. For the prime-power index INDEX_BASE^INDEX_POWER, this
+// basis coincides with the power basis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowerfulPoly(pub(crate) Box<[Residue; DEGREE]>);
+
+impl PowerfulPoly {
+    pub fn zero() -> PowerfulPoly {
+        PowerfulPoly(boxed_residues(Residue::zero()))
+    }
+
+    pub fn iter(&self) -> Iter<'_, Residue> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, Residue> {
+        self.0.iter_mut()
+    }
+}
+
+// Coefficients w.r.t. the decoding basis of the dual ideal, tweaked into the ring as in Lol. For
+// INDEX_BASE 2, this basis coincides with the powerful basis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodingPoly(pub(crate) Box<[Residue; DEGREE]>);
+
+impl DecodingPoly {
+    pub fn zero() -> DecodingPoly {
+        DecodingPoly(boxed_residues(Residue::zero()))
+    }
+
+    pub fn iter(&self) -> Iter<'_, Residue> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, Residue> {
+        self.0.iter_mut()
+    }
+}
+
+impl Distribution<ChremPoly> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ChremPoly {
+        let mut result = ChremPoly::zero();
+        for residue in result.iter_mut() {
+            *residue = rng.gen();
         }
-    } else if my_majors >= my_minors {
-        transpose_impl(
-            majors,
-            minors,
-            src,
-            dest,
-            my_majors_begin,
-            my_majors_begin + my_majors / 2,
-            my_minors_begin,
-            my_minors_end,
-        );
-        transpose_impl(
-            majors,
-            minors,
-            src,
-            dest,
-            my_majors_begin + my_majors / 2,
-            my_majors_end,
-            my_minors_begin,
-            my_minors_end,
-        );
-    } else {
-        transpose_impl(
-            majors,
-            minors,
-            src,
-            dest,
-            my_majors_begin,
-            my_majors_end,
-            my_minors_begin,
-            my_minors_begin + my_minors / 2,
-        );
-        transpose_impl(
-            majors,
-            minors,
-            src,
-            dest,
-            my_majors_begin,
-            my_majors_end,
-            my_minors_begin + my_minors / 2,
-            my_minors_end,
-        );
+        result
+    }
+}
+
+impl Distribution<PowerPoly> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> PowerPoly {
+        let mut result = PowerPoly::zero();
+        for residue in result.iter_mut() {
+            *residue = rng.gen();
+        }
+        result
+    }
+}
+
+impl Zeroize for ChremPoly {
+    fn zeroize(&mut self) {
+        self.0.as_mut().zeroize();
+    }
+}
+
+impl Zeroize for PowerPoly {
+    fn zeroize(&mut self) {
+        self.0.as_mut().zeroize();
+    }
+}
+
+impl Zeroize for PowerfulPoly {
+    fn zeroize(&mut self) {
+        self.0.as_mut().zeroize();
+    }
+}
+
+impl Zeroize for DecodingPoly {
+    fn zeroize(&mut self) {
+        self.0.as_mut().zeroize();
+    }
+}
+
+// Number of bytes in the encoding of a polynomial in either representation
+pub const POLY_BYTES: usize = DEGREE * RESIDUE_BYTES;
+
+fn encode_residues(residues: &[Residue]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(residues.len() * RESIDUE_BYTES);
+    for residue in residues {
+        result.extend_from_slice(&residue.to_bytes());
+    }
+    result
+}
+
+fn decode_residues(bytes: &[u8]) -> Result<Box<[Residue; DEGREE]>, Error> {
+    if bytes.len() != POLY_BYTES {
+        return Err(Error::Decode("wrong number of bytes for a polynomial"));
+    }
+    let mut result = boxed_residues(Residue::zero());
+    for (residue, chunk) in result.iter_mut().zip(bytes.chunks_exact(RESIDUE_BYTES)) {
+        *residue = Residue::from_bytes(chunk)?;
+    }
+    Ok(result)
+}
+
+// Encodes the coefficients in order.
+impl PowerPoly {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_residues(self.0.as_ref())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<PowerPoly, Error> {
+        decode_residues(bytes).map(PowerPoly)
+    }
+}
+
+// Encodes the slots in order.
+impl ChremPoly {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_residues(self.0.as_ref())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ChremPoly, Error> {
+        decode_residues(bytes).map(ChremPoly)
     }
 }
 
-fn transpose<T>(majors: usize, minors: usize, src: &[T], dest: &mut [T])
-where
-    T: Clone + PartialEq,
+// The length of the chunks into which the pointwise stage for index p * m' is split: m' divided by
+// a power of p, small enough for load balancing if possible.
+fn pointwise_chunk(p: usize, m_: usize) -> usize {
+    let mut chunk = m_;
+    while chunk > PARALLEL_CHUNK && chunk.is_multiple_of(p) {
+        chunk /= p;
+    }
+    chunk
+}
+
+// Coefficients of the transforms: residues modulo MODULUS, or modulo a word-sized prime for
+// batching plaintexts
+pub(crate) trait TransformRing:
+    Copy + Send + Sync + Sub<Output = Self> + Mul<Output = Self> + MulAssign
 {
-    assert!(src.len() == majors * minors);
-    assert!(src.len() == dest.len());
-    transpose_impl(majors, minors, src, dest, 0, majors, 0, minors);
+    fn from_u64(x: u64) -> Self;
+
+    fn inv(self) -> Self;
+
+    // Sum of the products of the pairs
+    fn dot<I: Iterator<Item = (Self, Self)>>(terms: I) -> Self;
+}
+
+// Reduces the sums lazily.
+impl TransformRing for Residue {
+    fn from_u64(x: u64) -> Residue {
+        Residue::from_u64(x)
+    }
+
+    fn inv(self) -> Residue {
+        Residue::inv(self)
+    }
+
+    fn dot<I: Iterator<Item = (Residue, Residue)>>(terms: I) -> Residue {
+        let mut sum = Accumulator::zero();
+        for (a, b) in terms {
+            sum.mul_acc(a, b);
+        }
+        sum.reduce()
+    }
+}
+
+// Powers of INDEX_TH_ROOT, i.e. roots[e] is INDEX_TH_ROOT^e for e in [INDEX_BASE^INDEX_POWER].
+// They are computed on first use and shared by all conversions.
+pub(crate) fn roots_of_unity() -> &'static [Residue] {
+    static ROOTS: OnceBox<Vec<Residue>> = OnceBox::new();
+    ROOTS.get_or_init(|| {
+        let roots = successors(Some(Residue::one()), |&root| Some(root * INDEX_TH_ROOT))
+            .take(INDEX_BASE.pow(INDEX_POWER))
+            .collect();
+        Box::new(roots)
+    })
+}
+
+fn root_pow<T: TransformRing>(roots: &[T], exponent: usize) -> T {
+    roots[exponent % roots.len()]
+}
+
+fn inv_root_pow<T: TransformRing>(roots: &[T], exponent: usize) -> T {
+    roots[(roots.len() - exponent % roots.len()) % roots.len()]
+}
+
+// The transforms only transpose buffers of matching lengths.
+fn transpose_buffers<T: TransformRing>(majors: usize, minors: usize, src: &[T], dest: &mut [T]) {
+    transpose(majors, minors, src, dest).expect("transform buffers have mismatched lengths");
 }
 
 // DFT for prime-power index
-fn dft(p: usize, power: u32, data: &mut [Residue], extra_buffer: &mut [Residue]) {
-    assert!(power >= 1);
+fn dft<T: TransformRing>(
+    p: usize,
+    power: u32,
+    roots: &[T],
+    data: &mut [T],
+    extra_buffer: &mut [T],
+    schedule: Schedule,
+) {
+    debug_assert!(power >= 1);
     let m_ = p.pow(power - 1);
     let m = m_ * p;
-    assert!(data.len() == m);
+    debug_assert!(data.len() == m);
+    // roots[stride] is a primitive m-th root of unity.
+    let stride = roots.len() / m;
     // T_m \cdot (DFT_p \otimes I_{[m']})
-    for j1 in 0..m_ {
-        for (j0_out, output) in extra_buffer.iter_mut().skip(j1).step_by(m_).enumerate() {
-            *output = Residue::zero();
-            // DFT_p \otimes I_{[m']}
-            for (j0_in, input) in data.iter().skip(j1).step_by(m_).enumerate() {
-                // TODO: Precompute the matrix with (i,j)th entry INDEX_TH_ROOT.pow(i*j).
-                *output += *input * INDEX_TH_ROOT.pow((j0_out * j0_in) as u32);
-            }
-            // T_m
-            if j1 > 0 && j0_out > 0 {
-                *output *= INDEX_TH_ROOT.pow((j0_out * j1) as u32);
+    let inputs = &*data;
+    for_each_chunk(
+        schedule,
+        extra_buffer,
+        pointwise_chunk(p, m_),
+        |offset, outputs| {
+            for (j, output) in outputs.iter_mut().enumerate() {
+                let (j0_out, j1) = ((offset + j) / m_, (offset + j) % m_);
+                // DFT_p \otimes I_{[m']}
+                *output =
+                    T::dot(inputs.iter().skip(j1).step_by(m_).enumerate().map(
+                        |(j0_in, &input)| (input, root_pow(roots, j0_out * j0_in * m_ * stride)),
+                    ));
+                // T_m
+                if j1 > 0 && j0_out > 0 {
+                    *output *= roots[j0_out * j1 * stride];
+                }
             }
-        }
-    }
+        },
+    );
     // I_{[p]} \otimes DFT_{m'}
     if power > 1 {
-        for start in (0..m).step_by(m_) {
-            let end = start + m_;
-            // Swap extra_buffer and data.
-            dft(
-                p,
-                power - 1,
-                &mut extra_buffer[start..end],
-                &mut data[start..end],
-            );
-        }
+        // Swap extra_buffer and data.
+        for_each_chunk_pair(schedule, extra_buffer, data, m_, |data, extra_buffer| {
+            dft(p, power - 1, roots, data, extra_buffer, schedule)
+        });
     }
-    transpose(p, m_, extra_buffer, data);
+    transpose_buffers(p, m_, extra_buffer, data);
 }
 
 // CRT for prime-power index
-fn crt(p: usize, power: u32, data: &mut [Residue], extra_buffer: &mut [Residue]) {
-    assert!(power >= 1);
+pub(crate) fn crt<T: TransformRing>(
+    p: usize,
+    power: u32,
+    roots: &[T],
+    data: &mut [T],
+    extra_buffer: &mut [T],
+    schedule: Schedule,
+) {
+    debug_assert!(power >= 1);
     let m_ = p.pow(power - 1);
     let totient = m_ * (p - 1);
-    assert!(data.len() == totient);
+    debug_assert!(data.len() == totient);
+    // roots[stride] is a primitive m-th root of unity.
+    let stride = roots.len() / (m_ * p);
     // \hat T_m \cdot (CRT_p \otimes I_{[m']})
-    for j1 in 0..m_ {
-        for (j0_out, output) in extra_buffer.iter_mut().skip(j1).step_by(m_).enumerate() {
-            *output = Residue::zero();
-            // CRT_p \otimes I_{[m']}
-            for (j0_in, input) in data.iter().skip(j1).step_by(m_).enumerate() {
-                // TODO: Precompute the matrix with (i,j)th entry INDEX_TH_ROOT.pow(i*j).
-                *output += *input * INDEX_TH_ROOT.pow(((j0_out + 1) * j0_in) as u32);
-            }
-            // \hat T_m
-            if j1 > 0 {
-                *output *= INDEX_TH_ROOT.pow(((j0_out + 1) * j1) as u32);
+    let inputs = &*data;
+    for_each_chunk(
+        schedule,
+        extra_buffer,
+        pointwise_chunk(p, m_),
+        |offset, outputs| {
+            for (j, output) in outputs.iter_mut().enumerate() {
+                let (j0_out, j1) = ((offset + j) / m_, (offset + j) % m_);
+                // CRT_p \otimes I_{[m']}
+                *output = T::dot(inputs.iter().skip(j1).step_by(m_).enumerate().map(
+                    |(j0_in, &input)| (input, root_pow(roots, (j0_out + 1) * j0_in * m_ * stride)),
+                ));
+                // \hat T_m
+                if j1 > 0 {
+                    *output *= roots[(j0_out + 1) * j1 * stride];
+                }
             }
-        }
-    }
+        },
+    );
     // I_{\mathbb Z_p^*} \otimes DFT_{m'}
     if power > 1 {
-        for start in (0..totient).step_by(m_) {
-            let end = start + m_;
-            // Swap extra_buffer and data.
-            dft(
-                p,
-                power - 1,
-                &mut extra_buffer[start..end],
-                &mut data[start..end],
-            );
-        }
+        // Swap extra_buffer and data.
+        for_each_chunk_pair(schedule, extra_buffer, data, m_, |data, extra_buffer| {
+            dft(p, power - 1, roots, data, extra_buffer, schedule)
+        });
     }
-    transpose(p - 1, m_, extra_buffer, data);
+    transpose_buffers(p - 1, m_, extra_buffer, data);
 }
 
-// Inverse DFT for prime-power index
-fn inv_dft(p: usize, power: u32, data: &mut [Residue], extra_buffer: &mut [Residue]) {
-    assert!(power >= 1);
+// Inverse DFT for prime-power index, scaled by m
+fn inv_dft<T: TransformRing>(
+    p: usize,
+    power: u32,
+    roots: &[T],
+    data: &mut [T],
+    extra_buffer: &mut [T],
+    schedule: Schedule,
+) {
+    debug_assert!(power >= 1);
     let m_ = p.pow(power - 1);
     let m = m_ * p;
-    assert!(data.len() == m);
-    transpose(m_, p, data, extra_buffer);
+    debug_assert!(data.len() == m);
+    // roots[stride] is a primitive m-th root of unity.
+    let stride = roots.len() / m;
+    transpose_buffers(m_, p, data, extra_buffer);
     // I_{[p]} \otimes DFT_{m'}^{-1}
     if power > 1 {
-        for start in (0..m).step_by(m_) {
-            let end = start + m_;
-            // Swap extra_buffer and data.
-            inv_dft(
-                p,
-                power - 1,
-                &mut extra_buffer[start..end],
-                &mut data[start..end],
-            );
-        }
+        // Swap extra_buffer and data.
+        for_each_chunk_pair(schedule, extra_buffer, data, m_, |data, extra_buffer| {
+            inv_dft(p, power - 1, roots, data, extra_buffer, schedule)
+        });
     }
-    // (DFT_p^{-1} \otimes I_{[m']}) \cdot T_m^{-1}
-    for j1 in 0..m_ {
-        // T_m^{-1}
-        if j1 > 0 {
-            for (j0_in, input) in extra_buffer
-                .iter_mut()
-                .skip(j1)
-                .step_by(m_)
-                .enumerate()
-                .skip(1)
-            {
-                *input *= INV_INDEX_TH_ROOT.pow((j0_in * j1) as u32);
-            }
-        }
-        // DFT_p^{-1} \otimes I_{[m']}
-        for (j0_out, output) in data.iter_mut().skip(j1).step_by(m_).enumerate() {
-            *output = Residue::zero();
-            for (j0_in, input) in extra_buffer.iter().skip(j1).step_by(m_).enumerate() {
-                // TODO: This is a HACK(!) for p = 2
-                if j0_out + j0_in == 1 {
-                    *output -= *input;
-                } else if j0_out + j0_in == 0 {
-                    *output += *input * INDEX_TH_ROOT;
-                } else {
-                    *output += *input;
+    // T_m^{-1}
+    for_each_chunk(
+        schedule,
+        extra_buffer,
+        pointwise_chunk(p, m_),
+        |offset, inputs| {
+            for (j, input) in inputs.iter_mut().enumerate() {
+                let (j0_in, j1) = ((offset + j) / m_, (offset + j) % m_);
+                if j1 > 0 && j0_in > 0 {
+                    *input *= inv_root_pow(roots, j0_in * j1 * stride);
                 }
             }
-            *output *= INV_INDEX_TH_ROOT_MINUS_ONE;
+        },
+    );
+    // DFT_p^{-1} \otimes I_{[m']}
+    let inputs = &*extra_buffer;
+    for_each_chunk(schedule, data, pointwise_chunk(p, m_), |offset, outputs| {
+        for (j, output) in outputs.iter_mut().enumerate() {
+            let (j0_out, j1) = ((offset + j) / m_, (offset + j) % m_);
+            *output = T::dot(
+                inputs
+                    .iter()
+                    .skip(j1)
+                    .step_by(m_)
+                    .enumerate()
+                    .map(|(j0_in, &input)| {
+                        (input, inv_root_pow(roots, j0_out * j0_in * m_ * stride))
+                    }),
+            );
         }
-    }
+    });
 }
 
 // Inverse CRT for prime-power index
-fn inv_crt(p: usize, power: u32, data: &mut [Residue], extra_buffer: &mut [Residue]) {
-    assert!(power >= 1);
+pub(crate) fn inv_crt<T: TransformRing>(
+    p: usize,
+    power: u32,
+    roots: &[T],
+    data: &mut [T],
+    extra_buffer: &mut [T],
+    schedule: Schedule,
+) {
+    debug_assert!(power >= 1);
     let m_ = p.pow(power - 1);
+    let m = m_ * p;
     let totient = m_ * (p - 1);
-    assert!(data.len() == totient);
-    transpose(m_, p - 1, data, extra_buffer);
+    debug_assert!(data.len() == totient);
+    // roots[stride] is a primitive m-th root of unity.
+    let stride = roots.len() / m;
+    transpose_buffers(m_, p - 1, data, extra_buffer);
     // I_{\mathbb Z_p^*} \otimes DFT_{m'}^{-1}
     if power > 1 {
-        for start in (0..totient).step_by(m_) {
-            let end = start + m_;
-            // Swap extra_buffer and data.
-            inv_dft(
-                p,
-                power - 1,
-                &mut extra_buffer[start..end],
-                &mut data[start..end],
-            );
+        // Swap extra_buffer and data.
+        for_each_chunk_pair(schedule, extra_buffer, data, m_, |data, extra_buffer| {
+            inv_dft(p, power - 1, roots, data, extra_buffer, schedule)
+        });
+    }
+    // \hat T_m^{-1}
+    for_each_chunk(
+        schedule,
+        extra_buffer,
+        pointwise_chunk(p, m_),
+        |offset, inputs| {
+            for (j, input) in inputs.iter_mut().enumerate() {
+                let (j0_in, j1) = ((offset + j) / m_, (offset + j) % m_);
+                if j1 > 0 {
+                    *input *= inv_root_pow(roots, (j0_in + 1) * j1 * stride);
+                }
+            }
+        },
+    );
+    // Both p CRT_p^{-1} = (I + J) CRT_p^* and the inverse DFTs above are scaled, by p and m'.
+    let scale = T::from_u64(m as u64).inv();
+    // CRT_p^{-1} \otimes I_{[m']}
+    let inputs = &*extra_buffer;
+    for_each_chunk(schedule, data, pointwise_chunk(p, m_), |offset, outputs| {
+        for (j, output) in outputs.iter_mut().enumerate() {
+            let (j0_out, j1) = ((offset + j) / m_, (offset + j) % m_);
+            let sum = T::dot(inputs.iter().skip(j1).step_by(m_).enumerate().map(
+                |(j0_in, &input)| {
+                    let entry = inv_root_pow(roots, (j0_in + 1) * j0_out * m_ * stride)
+                        - root_pow(roots, (j0_in + 1) * m_ * stride);
+                    (input, entry)
+                },
+            ));
+            *output = sum * scale;
+        }
+    });
+}
+
+// Decoding to powerful basis for prime-power index: L_p \otimes I_{[m']}, where L_p is the
+// lower-triangular (p - 1) x (p - 1) matrix of ones
+fn decoding_to_powerful(p: usize, power: u32, data: &mut [Residue]) {
+    debug_assert!(power >= 1);
+    let m_ = p.pow(power - 1);
+    debug_assert!(data.len() == m_ * (p - 1));
+    for j1 in 0..m_ {
+        let mut sum = Residue::zero();
+        for coeff in data.iter_mut().skip(j1).step_by(m_) {
+            sum += *coeff;
+            *coeff = sum;
         }
     }
-    // (CRT_p^{-1} \otimes I_{[m']}) \cdot \hat T_m^{-1}
+}
+
+// Powerful to decoding basis for prime-power index: L_p^{-1} \otimes I_{[m']}, where L_p^{-1} has
+// ones on the diagonal and minus ones below
+fn powerful_to_decoding(p: usize, power: u32, data: &mut [Residue]) {
+    debug_assert!(power >= 1);
+    let m_ = p.pow(power - 1);
+    debug_assert!(data.len() == m_ * (p - 1));
     for j1 in 0..m_ {
-        // \hat T_m^{-1}
-        if j1 > 0 {
-            for (j0_in, input) in extra_buffer.iter_mut().skip(j1).step_by(m_).enumerate() {
-                *input *= INV_INDEX_TH_ROOT.pow(((j0_in + 1) * j1) as u32);
-            }
+        let mut previous = Residue::zero();
+        for coeff in data.iter_mut().skip(j1).step_by(m_) {
+            let current = *coeff;
+            *coeff -= previous;
+            previous = current;
         }
-        // CRT_p^{-1} \otimes I_{[m']}
-        for (j0_out, output) in data.iter_mut().skip(j1).step_by(m_).enumerate() {
-            *output = Residue::zero();
-            for (j0_in, input) in extra_buffer.iter().skip(j1).step_by(m_).enumerate() {
-                // TODO: This is a HACK(!) for p = 2
-                *output += *input;
-            }
+    }
+}
+
+// Reusable scratch space for conversions between the power and CRT representations, so that
+// repeated conversions don't allocate
+pub struct Scratch {
+    buffer: Box<[Residue; DEGREE]>,
+}
+
+impl Scratch {
+    pub fn new() -> Scratch {
+        Scratch {
+            buffer: boxed_residues(Residue::zero()),
         }
     }
+
+    // Converts coefficients in the power basis to slots in place.
+    fn crt(&mut self, data: &mut [Residue]) {
+        let roots = roots_of_unity();
+        crt(
+            INDEX_BASE,
+            INDEX_POWER,
+            roots,
+            data,
+            self.buffer.as_mut(),
+            Schedule::Parallel,
+        );
+    }
+
+    // Converts slots to coefficients in the power basis in place.
+    fn inv_crt(&mut self, data: &mut [Residue]) {
+        let roots = roots_of_unity();
+        inv_crt(
+            INDEX_BASE,
+            INDEX_POWER,
+            roots,
+            data,
+            self.buffer.as_mut(),
+            Schedule::Parallel,
+        );
+    }
+}
+
+impl Default for Scratch {
+    fn default() -> Scratch {
+        Scratch::new()
+    }
+}
+
+// The buffer holds intermediate values of the last conversion, which may be secret.
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        self.buffer.as_mut().zeroize();
+    }
+}
+
+impl PowerPoly {
+    // Converts to the CRT representation, reusing this polynomial's memory.
+    pub fn into_crt_in_place(mut self, scratch: &mut Scratch) -> ChremPoly {
+        scratch.crt(self.0.as_mut());
+        ChremPoly(self.0)
+    }
+}
+
+impl ChremPoly {
+    // Converts to the power representation, reusing this polynomial's memory.
+    pub fn into_power_in_place(mut self, scratch: &mut Scratch) -> PowerPoly {
+        scratch.inv_crt(self.0.as_mut());
+        PowerPoly(self.0)
+    }
 }
 
 impl From<PowerPoly> for ChremPoly {
-    fn from(mut other: PowerPoly) -> ChremPoly {
-        let mut result = ChremPoly(other.0.clone());
-        crt(INDEX_BASE, INDEX_POWER, result.0.as_mut(), other.0.as_mut());
-        result
+    fn from(other: PowerPoly) -> ChremPoly {
+        other.into_crt_in_place(&mut Scratch::new())
     }
 }
 
 impl From<ChremPoly> for PowerPoly {
-    fn from(mut other: ChremPoly) -> PowerPoly {
-        let mut result = PowerPoly(other.0.clone());
-        inv_crt(INDEX_BASE, INDEX_POWER, result.0.as_mut(), other.0.as_mut());
-        result
+    fn from(other: ChremPoly) -> PowerPoly {
+        other.into_power_in_place(&mut Scratch::new())
+    }
+}
+
+impl From<PowerPoly> for PowerfulPoly {
+    fn from(other: PowerPoly) -> PowerfulPoly {
+        PowerfulPoly(other.0)
+    }
+}
+
+impl From<PowerfulPoly> for PowerPoly {
+    fn from(other: PowerfulPoly) -> PowerPoly {
+        PowerPoly(other.0)
+    }
+}
+
+impl From<DecodingPoly> for PowerfulPoly {
+    fn from(mut other: DecodingPoly) -> PowerfulPoly {
+        decoding_to_powerful(INDEX_BASE, INDEX_POWER, other.0.as_mut());
+        PowerfulPoly(other.0)
+    }
+}
+
+impl From<PowerfulPoly> for DecodingPoly {
+    fn from(mut other: PowerfulPoly) -> DecodingPoly {
+        powerful_to_decoding(INDEX_BASE, INDEX_POWER, other.0.as_mut());
+        DecodingPoly(other.0)
+    }
+}
+
+impl From<DecodingPoly> for PowerPoly {
+    fn from(other: DecodingPoly) -> PowerPoly {
+        PowerPoly::from(PowerfulPoly::from(other))
+    }
+}
+
+impl From<PowerPoly> for DecodingPoly {
+    fn from(other: PowerPoly) -> DecodingPoly {
+        DecodingPoly::from(PowerfulPoly::from(other))
     }
 }
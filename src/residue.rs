@@ -1,20 +1,39 @@
+use crate::error::Error;
+use alloc::boxed::Box;
+use core::convert::TryFrom;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use std::convert::{TryFrom, TryInto};
-use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use zeroize::DefaultIsZeroes;
 
-construct_uint! {
-    pub struct U384(6); // 6 x 64-bit word
-}
+// The expansions of construct_uint! trip some clippy lints.
+#[allow(clippy::assign_op_pattern, clippy::manual_range_contains)]
+mod bigint {
+    construct_uint! {
+        pub struct U384(6); // 6 x 64-bit word
+    }
 
-construct_uint! {
-    pub struct U448(7);
-}
+    construct_uint! {
+        pub struct U448(7);
+    }
+
+    construct_uint! {
+        pub struct U768(12);
+    }
+
+    // Holds sums of DEGREE squares of residues.
+    construct_uint! {
+        pub struct U832(13);
+    }
 
-construct_uint! {
-    pub struct U768(12);
+    // Holds exact products of polynomials with centered coefficients, scaled by a word.
+    construct_uint! {
+        pub struct U1024(16);
+    }
 }
 
+pub use self::bigint::{U1024, U384, U448, U768, U832};
+
 impl From<U384> for U448 {
     fn from(other: U384) -> U448 {
         let mut data = [0; 7];
@@ -24,16 +43,16 @@ impl From<U384> for U448 {
 }
 
 impl TryFrom<U448> for U384 {
-    type Error = &'static str;
+    type Error = Error;
 
-    fn try_from(other: U448) -> Result<U384, &'static str> {
+    fn try_from(other: U448) -> Result<U384, Error> {
         let data = other.as_ref();
         if data[6..].iter().all(|&x| x == 0) {
             let mut new_data = [0; 6];
             new_data.clone_from_slice(&data[..6]);
             Ok(U384(new_data))
         } else {
-            Err("Overflow during conversion from U448 to U384.")
+            Err(Error::OutOfRange)
         }
     }
 }
@@ -47,16 +66,16 @@ impl From<U384> for U768 {
 }
 
 impl TryFrom<U768> for U384 {
-    type Error = &'static str;
+    type Error = Error;
 
-    fn try_from(other: U768) -> Result<U384, &'static str> {
+    fn try_from(other: U768) -> Result<U384, Error> {
         let data = other.as_ref();
         if data[6..].iter().all(|&x| x == 0) {
             let mut new_data = [0; 6];
             new_data.clone_from_slice(&data[..6]);
             Ok(U384(new_data))
         } else {
-            Err("Overflow during conversion from U768 to U384.")
+            Err(Error::OutOfRange)
         }
     }
 }
@@ -69,17 +88,48 @@ impl From<U448> for U768 {
     }
 }
 
+impl From<U768> for U832 {
+    fn from(other: U768) -> U832 {
+        let mut data = [0; 13];
+        data[..12].clone_from_slice(other.0.as_ref());
+        U832(data)
+    }
+}
+
+impl From<U384> for U1024 {
+    fn from(other: U384) -> U1024 {
+        let mut data = [0; 16];
+        data[..6].clone_from_slice(other.0.as_ref());
+        U1024(data)
+    }
+}
+
+impl TryFrom<U1024> for U384 {
+    type Error = Error;
+
+    fn try_from(other: U1024) -> Result<U384, Error> {
+        let data = other.as_ref();
+        if data[6..].iter().all(|&x| x == 0) {
+            let mut new_data = [0; 6];
+            new_data.clone_from_slice(&data[..6]);
+            Ok(U384(new_data))
+        } else {
+            Err(Error::OutOfRange)
+        }
+    }
+}
+
 impl TryFrom<U768> for U448 {
-    type Error = &'static str;
+    type Error = Error;
 
-    fn try_from(other: U768) -> Result<U448, &'static str> {
+    fn try_from(other: U768) -> Result<U448, Error> {
         let data = other.as_ref();
         if data[7..].iter().all(|&x| x == 0) {
             let mut new_data = [0; 7];
             new_data.clone_from_slice(&data[..7]);
             Ok(U448(new_data))
         } else {
-            Err("Overflow during conversion from U768 to U448.")
+            Err(Error::OutOfRange)
         }
     }
 }
@@ -123,8 +173,104 @@ pub const INV_INDEX_TH_ROOT_MINUS_ONE: Residue = Residue(U384([
 ])); // 4461521010483510675518975853309313069104368336261712293894066426457300854164739821251883086262544700767491372522605
      // which is the multiplicative inverse of INDEX_TH_ROOT - 1.
 
+// Number of bytes in the encoding of a residue
+pub const RESIDUE_BYTES: usize = 48;
+
+// 2^384 - MODULUS, so that 2^384 is congruent to MODULUS_COMPLEMENT mod MODULUS.
+pub(crate) const MODULUS_COMPLEMENT: u64 = 1081343;
+
+// Computes lo + hi * MODULUS_COMPLEMENT, where lo has 6 limbs and hi has at most 6 limbs.
+fn fold(lo: &[u64], hi: &[u64]) -> [u64; 7] {
+    let mut result = [0; 7];
+    let mut carry = 0u128;
+    for (i, r) in result.iter_mut().take(6).enumerate() {
+        let high = hi
+            .get(i)
+            .map_or(0, |&h| h as u128 * MODULUS_COMPLEMENT as u128);
+        let sum = lo[i] as u128 + high + carry;
+        *r = sum as u64;
+        carry = sum >> 64;
+    }
+    result[6] = carry as u64;
+    result
+}
+
+// Reduces a little-endian integer of 6 to 12 limbs modulo MODULUS.
+fn reduce_limbs(limbs: &[u64]) -> U384 {
+    let (lo, hi) = limbs.split_at(6);
+    let mut folded = fold(lo, hi);
+    while folded[6] != 0 {
+        folded = fold(&folded[..6], &folded[6..]);
+    }
+    let mut data = [0; 6];
+    data.clone_from_slice(&folded[..6]);
+    let result = U384(data);
+    if result >= MODULUS {
+        result - MODULUS
+    } else {
+        result
+    }
+}
+
+// Unreduced sum of products of residues. Each product is below 2^768, so the 13 limbs can take
+// 2^64 products before overflowing. Only the final `reduce` is reduced modulo MODULUS.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Accumulator([u64; 13]);
+
+impl Accumulator {
+    pub fn zero() -> Accumulator {
+        Accumulator([0; 13])
+    }
+
+    // Adds a * b without reducing.
+    pub fn mul_acc(&mut self, a: Residue, b: Residue) {
+        let limbs = &mut self.0;
+        for (i, &a) in (a.0).0.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in (b.0).0.iter().enumerate() {
+                let sum = a as u128 * b as u128 + limbs[i + j] as u128 + carry;
+                limbs[i + j] = sum as u64;
+                carry = sum >> 64;
+            }
+            for limb in limbs.iter_mut().skip(i + 6) {
+                if carry == 0 {
+                    break;
+                }
+                let sum = *limb as u128 + carry;
+                *limb = sum as u64;
+                carry = sum >> 64;
+            }
+            debug_assert!(carry == 0);
+        }
+    }
+
+    // Adds a without reducing.
+    pub fn acc(&mut self, a: Residue) {
+        let mut carry = 0u128;
+        for (i, limb) in self.0.iter_mut().enumerate() {
+            let sum = *limb as u128 + (a.0).0.get(i).map_or(0, |&a| a as u128) + carry;
+            *limb = sum as u64;
+            carry = sum >> 64;
+        }
+        debug_assert!(carry == 0);
+    }
+
+    pub fn reduce(&self) -> Residue {
+        // 2^768 is congruent to MODULUS_COMPLEMENT^2 mod MODULUS.
+        Residue(reduce_limbs(&self.0[..12]))
+            + Residue::from_u64(self.0[12]) * (MODULUS_COMPLEMENT * MODULUS_COMPLEMENT)
+    }
+}
+
+// DEGREE copies of value, allocated directly on the heap. Box::new([value; DEGREE]) would build
+// the array on the stack first, which overflows small stacks once a few of them are inlined.
+pub fn boxed_residues(value: Residue) -> Box<[Residue; DEGREE]> {
+    let slice = alloc::vec![value; DEGREE].into_boxed_slice();
+    Box::<[Residue; DEGREE]>::try_from(slice).unwrap_or_else(|_| unreachable!())
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Residue(pub U384);
+pub struct Residue(pub(crate) U384);
 
 impl Residue {
     pub fn zero() -> Residue {
@@ -147,6 +293,61 @@ impl Residue {
         }
     }
 
+    // The canonical representative, below MODULUS
+    pub fn to_u384(self) -> U384 {
+        self.0
+    }
+
+    // Little-endian encoding of the canonical representative
+    pub fn to_bytes(self) -> [u8; RESIDUE_BYTES] {
+        let mut result = [0; RESIDUE_BYTES];
+        for (chunk, limb) in result.chunks_exact_mut(8).zip((self.0).0.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        result
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Residue, Error> {
+        if bytes.len() != RESIDUE_BYTES {
+            return Err(Error::Decode("wrong number of bytes for a residue"));
+        }
+        let mut data = [0; 6];
+        for (limb, chunk) in data.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut limb_bytes = [0; 8];
+            limb_bytes.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(limb_bytes);
+        }
+        Residue::try_from(U384(data))
+    }
+
+    // The representative in (-MODULUS / 2, MODULUS / 2]
+    pub fn to_centered(self) -> Centered {
+        if self.0 > MODULUS >> 1 {
+            Centered {
+                negative: true,
+                magnitude: MODULUS - self.0,
+            }
+        } else {
+            Centered {
+                negative: false,
+                magnitude: self.0,
+            }
+        }
+    }
+
+    pub fn inv(self) -> Residue {
+        // Fermat's little theorem, since MODULUS is prime.
+        let exponent = MODULUS - U384::from(2);
+        let mut result = Residue::one();
+        for i in (0..exponent.bits()).rev() {
+            result *= result;
+            if exponent.bit(i) {
+                result *= self;
+            }
+        }
+        result
+    }
+
     pub fn pow(mut self, mut exponent: u32) -> Residue {
         let mut result = Residue::one();
         while exponent > 0 {
@@ -160,20 +361,92 @@ impl Residue {
     }
 }
 
+// Signed integer as sign and magnitude, with a nonnegative zero
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Centered {
+    pub negative: bool,
+    pub magnitude: U384,
+}
+
+impl Centered {
+    // None if the value doesn't fit.
+    pub fn to_i128(self) -> Option<i128> {
+        if self.magnitude.bits() > 127 {
+            return None;
+        }
+        let magnitude = self.magnitude.low_u128() as i128;
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    // The nearest double, up to rounding in the summation
+    pub fn to_f64(self) -> f64 {
+        let magnitude = self.magnitude.0.iter().rev().fold(0.0, |value, &limb| {
+            value * 18446744073709551616.0 + limb as f64
+        });
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl From<Centered> for Residue {
+    fn from(centered: Centered) -> Residue {
+        let residue = Residue(centered.magnitude % MODULUS);
+        if centered.negative {
+            -residue
+        } else {
+            residue
+        }
+    }
+}
+
+// Fails unless the value is the canonical representative, i.e. below MODULUS.
+impl TryFrom<U384> for Residue {
+    type Error = Error;
+
+    fn try_from(value: U384) -> Result<Residue, Error> {
+        if value < MODULUS {
+            Ok(Residue(value))
+        } else {
+            Err(Error::OutOfRange)
+        }
+    }
+}
+
+impl Default for Residue {
+    fn default() -> Residue {
+        Residue::zero()
+    }
+}
+
+// Residues and slices of them can be zeroized, e.g. when they hold secrets.
+impl DefaultIsZeroes for Residue {}
+
+impl DefaultIsZeroes for Accumulator {}
+
 impl Neg for Residue {
     type Output = Residue;
 
     fn neg(mut self) -> Residue {
-        self.0 = MODULUS - self.0;
+        if !self.0.is_zero() {
+            self.0 = MODULUS - self.0;
+        }
         self
     }
 }
 
 impl AddAssign for Residue {
     fn add_assign(&mut self, other: Residue) {
-        let q = Into::<U448>::into(MODULUS);
-        let sum = Into::<U448>::into(self.0) + Into::<U448>::into(other.0);
-        self.0 = (sum % q).try_into().unwrap();
+        let (sum, overflow) = self.0.overflowing_add(other.0);
+        self.0 = if overflow {
+            sum + MODULUS_COMPLEMENT
+        } else if sum >= MODULUS {
+            sum - MODULUS
+        } else {
+            sum
+        };
     }
 }
 
@@ -188,9 +461,12 @@ impl Add for Residue {
 
 impl SubAssign for Residue {
     fn sub_assign(&mut self, other: Residue) {
-        let q = Into::<U448>::into(MODULUS);
-        let diff = q + Into::<U448>::into(self.0) - Into::<U448>::into(other.0);
-        self.0 = (diff % q).try_into().unwrap();
+        let (diff, underflow) = self.0.overflowing_sub(other.0);
+        self.0 = if underflow {
+            diff - MODULUS_COMPLEMENT
+        } else {
+            diff
+        };
     }
 }
 
@@ -205,9 +481,17 @@ impl Sub for Residue {
 
 impl MulAssign for Residue {
     fn mul_assign(&mut self, other: Residue) {
-        let q = Into::<U768>::into(MODULUS);
-        let prod = Into::<U768>::into(self.0) * Into::<U768>::into(other.0);
-        self.0 = (prod % q).try_into().unwrap();
+        let mut prod = [0; 12];
+        for (i, &a) in (self.0).0.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in (other.0).0.iter().enumerate() {
+                let sum = a as u128 * b as u128 + prod[i + j] as u128 + carry;
+                prod[i + j] = sum as u64;
+                carry = sum >> 64;
+            }
+            prod[i + 6] = carry as u64;
+        }
+        self.0 = reduce_limbs(&prod);
     }
 }
 
@@ -222,9 +506,8 @@ impl Mul for Residue {
 
 impl MulAssign<u64> for Residue {
     fn mul_assign(&mut self, other: u64) {
-        let q = Into::<U448>::into(MODULUS);
         let prod = Into::<U448>::into(self.0) * other;
-        self.0 = (prod % q).try_into().unwrap();
+        self.0 = reduce_limbs(&prod.0);
     }
 }
 
@@ -247,13 +530,11 @@ impl Mul<Residue> for u64 {
 
 impl MulAssign<i64> for Residue {
     fn mul_assign(&mut self, other: i64) {
-        let q = Into::<U448>::into(MODULUS);
-        let prod = if other >= 0 {
-            Into::<U448>::into(self.0) * other
+        if other >= 0 {
+            *self *= other as u64;
         } else {
-            (q - Into::<U448>::into(self.0)) * ((-other) as u64)
-        };
-        self.0 = (prod % q).try_into().unwrap();
+            *self = -*self * other.unsigned_abs();
+        }
     }
 }
 
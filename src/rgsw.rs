@@ -0,0 +1,69 @@
+use crate::poly::*;
+use crate::residue::*;
+use crate::rlwe::*;
+use alloc::vec::Vec;
+use rand::Rng;
+use zeroize::Zeroizing;
+
+// RGSW ciphertext of m, in CRT representation: GADGET_LEVELS rows with phase
+// e - m * 2^(k * GADGET_LOG_BASE) * s, followed by GADGET_LEVELS rows with phase
+// e + m * 2^(k * GADGET_LOG_BASE), for k in [GADGET_LEVELS]
+pub struct RgswCiphertext(Vec<(ChremPoly, ChremPoly)>);
+
+impl RgswCiphertext {
+    pub fn encrypt<R: Rng + ?Sized>(
+        key: &SecretKey,
+        message: &PowerPoly,
+        rng: &mut R,
+    ) -> RgswCiphertext {
+        let mut rows = Vec::with_capacity(2 * GADGET_LEVELS);
+        for times_key in [true, false].iter() {
+            for level in 0..GADGET_LEVELS {
+                let factor = Residue(U384::one() << (level * GADGET_LOG_BASE));
+                let scaled = Zeroizing::new(message.clone() * factor);
+                let mut row = RlweCiphertext::encrypt(key, &PowerPoly::zero(), rng);
+                if *times_key {
+                    row.a += &scaled;
+                } else {
+                    row.b += &scaled;
+                }
+                rows.push((ChremPoly::from(row.a), ChremPoly::from(row.b)));
+            }
+        }
+        RgswCiphertext(rows)
+    }
+
+    // Multiplies the phase of the RLWE ciphertext by the message.
+    pub fn external_product(&self, ciphertext: &RlweCiphertext) -> RlweCiphertext {
+        self.external_product_with(ciphertext, &mut Scratch::new())
+    }
+
+    pub fn external_product_with(
+        &self,
+        ciphertext: &RlweCiphertext,
+        scratch: &mut Scratch,
+    ) -> RlweCiphertext {
+        // The digits of a pair with the first half of the rows, the digits of b with the second.
+        let mut digits = Vec::with_capacity(2 * GADGET_LEVELS);
+        for poly in [&ciphertext.a, &ciphertext.b].iter() {
+            for level in 0..GADGET_LEVELS {
+                digits.push(gadget_digit(poly, level).into_crt_in_place(scratch));
+            }
+        }
+        let a = ChremPoly::inner_product(digits.iter().zip(self.0.iter().map(|(a, _)| a)));
+        let b = ChremPoly::inner_product(digits.iter().zip(self.0.iter().map(|(_, b)| b)));
+        RlweCiphertext {
+            a: a.into_power_in_place(scratch),
+            b: b.into_power_in_place(scratch),
+        }
+    }
+
+    // Selects if_true if the message is 1 and if_false if it is 0.
+    pub fn cmux(&self, if_false: &RlweCiphertext, if_true: &RlweCiphertext) -> RlweCiphertext {
+        let mut diff = if_true.clone();
+        diff -= if_false;
+        let mut result = self.external_product(&diff);
+        result += if_false;
+        result
+    }
+}
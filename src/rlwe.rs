@@ -0,0 +1,364 @@
+use crate::error::Error;
+use crate::poly::*;
+use crate::residue::*;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::ops::{AddAssign, SubAssign};
+use rand::Rng;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+// The gadget decomposes each coefficient into GADGET_LEVELS digits of GADGET_LOG_BASE bits.
+pub const GADGET_LOG_BASE: usize = 128;
+pub const GADGET_LEVELS: usize = 3;
+
+// Errors follow a centered binomial distribution with variance ERROR_ETA / 2.
+const ERROR_ETA: u32 = 20;
+pub const ERROR_VARIANCE: f64 = ERROR_ETA as f64 / 2.0;
+
+fn sample_error<R: Rng + ?Sized>(rng: &mut R) -> Residue {
+    let mask = (1u32 << ERROR_ETA) - 1;
+    let positive = (rng.gen::<u32>() & mask).count_ones();
+    let negative = (rng.gen::<u32>() & mask).count_ones();
+    Residue::from_i64(positive as i64 - negative as i64)
+}
+
+// Samples the error coefficients w.r.t. the decoding basis. The conversion reuses the memory.
+pub(crate) fn sample_error_poly<R: Rng + ?Sized>(rng: &mut R) -> Zeroizing<PowerPoly> {
+    let mut result = DecodingPoly::zero();
+    for coeff in result.iter_mut() {
+        *coeff = sample_error(rng);
+    }
+    Zeroizing::new(PowerPoly::from(result))
+}
+
+// Digit `level` of the gadget decomposition of each coefficient
+pub(crate) fn gadget_digit(poly: &PowerPoly, level: usize) -> PowerPoly {
+    let mask = (U384::one() << GADGET_LOG_BASE) - U384::one();
+    let mut result = poly.clone();
+    for coeff in result.iter_mut() {
+        coeff.0 = (coeff.0 >> (level * GADGET_LOG_BASE)) & mask;
+    }
+    result
+}
+
+// Message polynomial with coefficients modulo `modulus`, encoded in the phase as scale * m with
+// scale = floor(MODULUS / modulus)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plaintext {
+    modulus: u64,
+    coeffs: Vec<u64>,
+}
+
+impl Plaintext {
+    // Pads the coefficients with zeroes.
+    pub fn new(modulus: u64, coeffs: &[u64]) -> Result<Plaintext, Error> {
+        check_plaintext_modulus(modulus)?;
+        if coeffs.len() > DEGREE {
+            return Err(Error::LengthMismatch {
+                expected: DEGREE,
+                actual: coeffs.len(),
+            });
+        }
+        if coeffs.iter().any(|&coeff| coeff >= modulus) {
+            return Err(Error::OutOfRange);
+        }
+        let mut padded = vec![0; DEGREE];
+        padded[..coeffs.len()].copy_from_slice(coeffs);
+        Ok(Plaintext {
+            modulus,
+            coeffs: padded,
+        })
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    pub fn coeffs(&self) -> &[u64] {
+        &self.coeffs
+    }
+
+    pub fn scale(modulus: u64) -> Residue {
+        Residue(MODULUS / U384::from(modulus))
+    }
+
+    pub fn encode(&self) -> PowerPoly {
+        let scale = Plaintext::scale(self.modulus);
+        let mut result = PowerPoly::zero();
+        for (residue, &coeff) in result.iter_mut().zip(self.coeffs.iter()) {
+            *residue = scale * coeff;
+        }
+        result
+    }
+
+    // Rounds each coefficient of the phase to the nearest multiple of the scale.
+    pub fn decode(phase: &PowerPoly, modulus: u64) -> Result<Plaintext, Error> {
+        check_plaintext_modulus(modulus)?;
+        let q = U448::from(MODULUS);
+        let coeffs = phase
+            .iter()
+            .map(|residue| {
+                // round(residue * modulus / MODULUS), which is below 2^64 + 1
+                let rounded = (U448::from(residue.0) * modulus + (q >> 1)) / q;
+                (rounded % U448::from(modulus)).low_u64()
+            })
+            .collect();
+        Ok(Plaintext { modulus, coeffs })
+    }
+}
+
+pub(crate) fn check_plaintext_modulus(modulus: u64) -> Result<(), Error> {
+    if modulus < 2 {
+        return Err(Error::ParameterMismatch(
+            "the plaintext modulus must be at least 2",
+        ));
+    }
+    Ok(())
+}
+
+// Ternary secret key, kept in both representations. It is zeroized on drop and can't be cloned.
+pub struct SecretKey {
+    power: PowerPoly,
+    chrem: ChremPoly,
+}
+
+impl SecretKey {
+    pub fn generate<R: Rng + ?Sized>(rng: &mut R) -> SecretKey {
+        let mut power = PowerPoly::zero();
+        for coeff in power.iter_mut() {
+            *coeff = Residue::from_i64(rng.gen_range(-1, 2));
+        }
+        SecretKey::from(power)
+    }
+
+    pub(crate) fn power(&self) -> &PowerPoly {
+        &self.power
+    }
+
+    // s^2, which is what relinearization switches away from
+    pub fn squared(&self) -> SecretKey {
+        SecretKey::from(PowerPoly::from(self.chrem.clone() * &self.chrem))
+    }
+
+    // The image of the secret key under X -> X^exponent
+    pub fn automorphism(&self, exponent: usize) -> SecretKey {
+        SecretKey::from(self.power.automorphism(exponent))
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.power.zeroize();
+        self.chrem.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretKey {}
+
+impl Debug for SecretKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SecretKey(<redacted>)")
+    }
+}
+
+impl From<PowerPoly> for SecretKey {
+    fn from(power: PowerPoly) -> SecretKey {
+        let chrem = ChremPoly::from(power.clone());
+        SecretKey { power, chrem }
+    }
+}
+
+// RLWE ciphertext (a, b) with phase b - a * s
+#[derive(Clone, Debug, PartialEq)]
+pub struct RlweCiphertext {
+    pub a: PowerPoly,
+    pub b: PowerPoly,
+}
+
+impl RlweCiphertext {
+    pub fn encrypt<R: Rng + ?Sized>(
+        key: &SecretKey,
+        message: &PowerPoly,
+        rng: &mut R,
+    ) -> RlweCiphertext {
+        let a: PowerPoly = rng.gen();
+        let a_s = ChremPoly::from(a.clone()) * &key.chrem;
+        let b = PowerPoly::from(a_s) + &sample_error_poly(rng) + message;
+        RlweCiphertext { a, b }
+    }
+
+    // Noiseless encryption of `message` under every key
+    pub fn trivial(message: PowerPoly) -> RlweCiphertext {
+        RlweCiphertext {
+            a: PowerPoly::zero(),
+            b: message,
+        }
+    }
+
+    pub fn phase(&self, key: &SecretKey) -> PowerPoly {
+        let a_s = ChremPoly::from(self.a.clone()) * &key.chrem;
+        self.b.clone() - &Zeroizing::new(PowerPoly::from(a_s))
+    }
+
+    // Applies X -> X^exponent to the phase. The result is encrypted under the key's image.
+    pub fn automorphism(&self, exponent: usize) -> RlweCiphertext {
+        RlweCiphertext {
+            a: self.a.automorphism(exponent),
+            b: self.b.automorphism(exponent),
+        }
+    }
+
+    // Multiplies the phase by X^exponent.
+    pub fn mul_monomial(&self, exponent: usize) -> RlweCiphertext {
+        RlweCiphertext {
+            a: self.a.mul_monomial(exponent),
+            b: self.b.mul_monomial(exponent),
+        }
+    }
+
+    // LWE encryption of the phase's coefficient at `index`
+    pub fn sample_extract(&self, index: usize) -> LweCiphertext {
+        let mut a = boxed_residues(Residue::zero());
+        for (i, a_i) in a.iter_mut().enumerate() {
+            *a_i = if i <= index {
+                self.a[index - i]
+            } else {
+                -self.a[DEGREE + index - i]
+            };
+        }
+        LweCiphertext {
+            a,
+            b: self.b[index],
+        }
+    }
+}
+
+// Encodes a followed by b.
+impl RlweCiphertext {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = self.a.to_bytes();
+        result.extend(self.b.to_bytes());
+        result
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<RlweCiphertext, Error> {
+        if bytes.len() != 2 * POLY_BYTES {
+            return Err(Error::Decode(
+                "wrong number of bytes for an RLWE ciphertext",
+            ));
+        }
+        let (a, b) = bytes.split_at(POLY_BYTES);
+        Ok(RlweCiphertext {
+            a: PowerPoly::from_bytes(a)?,
+            b: PowerPoly::from_bytes(b)?,
+        })
+    }
+}
+
+impl AddAssign<&RlweCiphertext> for RlweCiphertext {
+    fn add_assign(&mut self, other: &RlweCiphertext) {
+        self.a += &other.a;
+        self.b += &other.b;
+    }
+}
+
+impl SubAssign<&RlweCiphertext> for RlweCiphertext {
+    fn sub_assign(&mut self, other: &RlweCiphertext) {
+        self.a -= &other.a;
+        self.b -= &other.b;
+    }
+}
+
+// Embeds the LWE phase into the constant coefficient of the RLWE phase.
+impl From<&LweCiphertext> for RlweCiphertext {
+    fn from(other: &LweCiphertext) -> RlweCiphertext {
+        let mut a = PowerPoly::zero();
+        a[0] = other.a[0];
+        for (i, a_i) in other.a.iter().enumerate().skip(1) {
+            a[DEGREE - i] = -*a_i;
+        }
+        let mut b = PowerPoly::zero();
+        b[0] = other.b;
+        RlweCiphertext { a, b }
+    }
+}
+
+// LWE ciphertext (a, b) with phase b - <a, s>, where s are the secret key's coefficients
+#[derive(Clone, Debug, PartialEq)]
+pub struct LweCiphertext {
+    pub a: Box<[Residue; DEGREE]>,
+    pub b: Residue,
+}
+
+impl LweCiphertext {
+    pub fn encrypt<R: Rng + ?Sized>(
+        key: &SecretKey,
+        message: Residue,
+        rng: &mut R,
+    ) -> LweCiphertext {
+        let mut a = boxed_residues(Residue::zero());
+        for a_i in a.iter_mut() {
+            *a_i = rng.gen();
+        }
+        let mut sum = Zeroizing::new(Accumulator::zero());
+        for (a_i, s_i) in a.iter().zip(key.power.iter()) {
+            sum.mul_acc(*a_i, *s_i);
+        }
+        let b = message + sample_error(rng) + sum.reduce();
+        LweCiphertext { a, b }
+    }
+
+    pub fn phase(&self, key: &SecretKey) -> Residue {
+        let mut sum = Zeroizing::new(Accumulator::zero());
+        for (a_i, s_i) in self.a.iter().zip(key.power.iter()) {
+            sum.mul_acc(*a_i, *s_i);
+        }
+        self.b - sum.reduce()
+    }
+}
+
+// Gadget encryptions of the old key under the new key, in CRT representation
+pub struct KeySwitchingKey(Vec<(ChremPoly, ChremPoly)>);
+
+impl KeySwitchingKey {
+    pub fn generate<R: Rng + ?Sized>(
+        from: &SecretKey,
+        to: &SecretKey,
+        rng: &mut R,
+    ) -> KeySwitchingKey {
+        let mut levels = Vec::with_capacity(GADGET_LEVELS);
+        for level in 0..GADGET_LEVELS {
+            let factor = Residue(U384::one() << (level * GADGET_LOG_BASE));
+            // A uniform polynomial is uniform in either representation.
+            let a: ChremPoly = rng.gen();
+            // The message's memory ends up holding b, but a * s_to needs to be cleared.
+            let message = from.power.clone() * factor + &sample_error_poly(rng);
+            let b = ChremPoly::from(message) + &Zeroizing::new(a.clone() * &to.chrem);
+            levels.push((a, b));
+        }
+        KeySwitchingKey(levels)
+    }
+
+    // Turns an encryption under the old key into an encryption of the same phase under the new key.
+    pub fn switch(&self, ciphertext: &RlweCiphertext) -> RlweCiphertext {
+        self.switch_with(ciphertext, &mut Scratch::new())
+    }
+
+    pub fn switch_with(
+        &self,
+        ciphertext: &RlweCiphertext,
+        scratch: &mut Scratch,
+    ) -> RlweCiphertext {
+        let digits: Vec<ChremPoly> = (0..GADGET_LEVELS)
+            .map(|level| gadget_digit(&ciphertext.a, level).into_crt_in_place(scratch))
+            .collect();
+        let a = ChremPoly::inner_product(digits.iter().zip(self.0.iter().map(|(a, _)| a)));
+        let b = ChremPoly::inner_product(digits.iter().zip(self.0.iter().map(|(_, b)| b)));
+        RlweCiphertext {
+            a: -a.into_power_in_place(scratch),
+            b: ciphertext.b.clone() - &b.into_power_in_place(scratch),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::residue::*;
+
+// Slot-wise arithmetic on residue vectors. On x86_64 with AVX2, which is detected at runtime, four
+// residues are processed at once. Otherwise, this falls back to the scalar Residue arithmetic.
+
+pub fn add_assign(a: &mut [Residue], b: &[Residue]) {
+    assert!(a.len() == b.len());
+    #[cfg(target_arch = "x86_64")]
+    {
+        if avx2::is_available() {
+            // SAFETY: AVX2 is available, and the lengths were checked above.
+            unsafe { avx2::add_assign(a, b) };
+            return;
+        }
+    }
+    scalar::add_assign(a, b);
+}
+
+pub fn sub_assign(a: &mut [Residue], b: &[Residue]) {
+    assert!(a.len() == b.len());
+    #[cfg(target_arch = "x86_64")]
+    {
+        if avx2::is_available() {
+            // SAFETY: AVX2 is available, and the lengths were checked above.
+            unsafe { avx2::sub_assign(a, b) };
+            return;
+        }
+    }
+    scalar::sub_assign(a, b);
+}
+
+pub fn mul_assign(a: &mut [Residue], b: &[Residue]) {
+    assert!(a.len() == b.len());
+    #[cfg(target_arch = "x86_64")]
+    {
+        if avx2::is_available() {
+            // SAFETY: AVX2 is available, and the lengths were checked above.
+            unsafe { avx2::mul_assign(a, b) };
+            return;
+        }
+    }
+    scalar::mul_assign(a, b);
+}
+
+pub mod scalar {
+    use crate::residue::*;
+
+    pub fn add_assign(a: &mut [Residue], b: &[Residue]) {
+        for (a, b) in a.iter_mut().zip(b.iter()) {
+            *a += *b;
+        }
+    }
+
+    pub fn sub_assign(a: &mut [Residue], b: &[Residue]) {
+        for (a, b) in a.iter_mut().zip(b.iter()) {
+            *a -= *b;
+        }
+    }
+
+    pub fn mul_assign(a: &mut [Residue], b: &[Residue]) {
+        for (a, b) in a.iter_mut().zip(b.iter()) {
+            *a *= *b;
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub mod avx2 {
+    use super::scalar;
+    use crate::residue::*;
+    use core::arch::x86_64::*;
+
+    // Each residue is split into LIMBS 32-bit limbs. Limb k of LANES residues is held in the 64-bit
+    // lanes of one vector, which leaves room for carries and for 32 x 32-bit products.
+    const LANES: usize = 4;
+    const LIMBS: usize = 12;
+
+    type Limbs = [__m256i; LIMBS];
+
+    // Runtime detection needs std. Without it, AVX2 is only used if enabled at compile time.
+    #[cfg(feature = "std")]
+    pub fn is_available() -> bool {
+        is_x86_feature_detected!("avx2")
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub fn is_available() -> bool {
+        cfg!(target_feature = "avx2")
+    }
+
+    // # Safety
+    //
+    // The following functions may only be called if AVX2 is available, see is_available. The
+    // slices must have equal lengths.
+
+    pub unsafe fn add_assign(a: &mut [Residue], b: &[Residue]) {
+        for_each_batch(a, b, add_batch, scalar::add_assign);
+    }
+
+    pub unsafe fn sub_assign(a: &mut [Residue], b: &[Residue]) {
+        for_each_batch(a, b, sub_batch, scalar::sub_assign);
+    }
+
+    pub unsafe fn mul_assign(a: &mut [Residue], b: &[Residue]) {
+        for_each_batch(a, b, mul_batch, scalar::mul_assign);
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn for_each_batch(
+        a: &mut [Residue],
+        b: &[Residue],
+        batch: unsafe fn(&Limbs, &Limbs) -> Limbs,
+        remainder: fn(&mut [Residue], &[Residue]),
+    ) {
+        debug_assert_eq!(a.len(), b.len());
+        let batches = a.len() / LANES * LANES;
+        let (a_batches, a_rest) = a.split_at_mut(batches);
+        let (b_batches, b_rest) = b.split_at(batches);
+        for (a, b) in a_batches
+            .chunks_exact_mut(LANES)
+            .zip(b_batches.chunks_exact(LANES))
+        {
+            let result = batch(&load(a), &load(b));
+            store(&result, a);
+        }
+        remainder(a_rest, b_rest);
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn low_mask() -> __m256i {
+        _mm256_set1_epi64x(0xFFFF_FFFF)
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn load(residues: &[Residue]) -> Limbs {
+        let mut result = [_mm256_setzero_si256(); LIMBS];
+        for k in 0..LIMBS / 2 {
+            let words = _mm256_set_epi64x(
+                (residues[3].0).0[k] as i64,
+                (residues[2].0).0[k] as i64,
+                (residues[1].0).0[k] as i64,
+                (residues[0].0).0[k] as i64,
+            );
+            result[2 * k] = _mm256_and_si256(words, low_mask());
+            result[2 * k + 1] = _mm256_srli_epi64(words, 32);
+        }
+        result
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn store(limbs: &Limbs, residues: &mut [Residue]) {
+        for k in 0..LIMBS / 2 {
+            let words = _mm256_or_si256(limbs[2 * k], _mm256_slli_epi64(limbs[2 * k + 1], 32));
+            let mut lanes = [0u64; LANES];
+            _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, words);
+            for (residue, &word) in residues.iter_mut().zip(lanes.iter()) {
+                (residue.0).0[k] = word;
+            }
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn modulus() -> Limbs {
+        let mut result = [_mm256_setzero_si256(); LIMBS];
+        for (k, limb) in result.iter_mut().enumerate() {
+            let word = MODULUS.0[k / 2] >> (32 * (k % 2));
+            *limb = _mm256_set1_epi64x((word & 0xFFFF_FFFF) as i64);
+        }
+        result
+    }
+
+    // Propagates carries so that all limbs are below 2^32, and returns the carry out of the top.
+    #[target_feature(enable = "avx2")]
+    unsafe fn propagate(limbs: &mut [__m256i]) -> __m256i {
+        let mut carry = _mm256_setzero_si256();
+        for limb in limbs.iter_mut() {
+            let sum = _mm256_add_epi64(*limb, carry);
+            carry = _mm256_srli_epi64(sum, 32);
+            *limb = _mm256_and_si256(sum, low_mask());
+        }
+        carry
+    }
+
+    // a - b, together with the borrow out of the top limb
+    #[target_feature(enable = "avx2")]
+    unsafe fn sub_limbs(a: &Limbs, b: &Limbs) -> (Limbs, __m256i) {
+        let mut result = [_mm256_setzero_si256(); LIMBS];
+        let mut borrow = _mm256_setzero_si256();
+        for k in 0..LIMBS {
+            let diff = _mm256_sub_epi64(_mm256_sub_epi64(a[k], b[k]), borrow);
+            borrow = _mm256_srli_epi64(diff, 63);
+            result[k] = _mm256_and_si256(diff, low_mask());
+        }
+        (result, borrow)
+    }
+
+    // Lane-wise if mask { a } else { b }
+    #[target_feature(enable = "avx2")]
+    unsafe fn select(mask: __m256i, a: &Limbs, b: &Limbs) -> Limbs {
+        let mut result = [_mm256_setzero_si256(); LIMBS];
+        for k in 0..LIMBS {
+            result[k] = _mm256_blendv_epi8(b[k], a[k], mask);
+        }
+        result
+    }
+
+    // Reduces x + carry * 2^384, which must be less than 2 * MODULUS.
+    #[target_feature(enable = "avx2")]
+    unsafe fn reduce_once(x: &Limbs, carry: __m256i) -> Limbs {
+        let (diff, borrow) = sub_limbs(x, &modulus());
+        // x + carry * 2^384 < MODULUS iff the subtraction borrows beyond the carry.
+        select(_mm256_cmpgt_epi64(borrow, carry), x, &diff)
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn add_batch(a: &Limbs, b: &Limbs) -> Limbs {
+        let mut sum = [_mm256_setzero_si256(); LIMBS];
+        for k in 0..LIMBS {
+            sum[k] = _mm256_add_epi64(a[k], b[k]);
+        }
+        let carry = propagate(&mut sum);
+        reduce_once(&sum, carry)
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn sub_batch(a: &Limbs, b: &Limbs) -> Limbs {
+        let (diff, borrow) = sub_limbs(a, b);
+        let q = modulus();
+        let mut corrected = [_mm256_setzero_si256(); LIMBS];
+        for k in 0..LIMBS {
+            corrected[k] = _mm256_add_epi64(diff[k], q[k]);
+        }
+        propagate(&mut corrected);
+        select(
+            _mm256_cmpeq_epi64(borrow, _mm256_set1_epi64x(1)),
+            &corrected,
+            &diff,
+        )
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn mul_batch(a: &Limbs, b: &Limbs) -> Limbs {
+        // Schoolbook product, accumulating the halves of each 64-bit partial product in separate
+        // columns. Each column receives at most 2 * LIMBS summands below 2^32.
+        let mut columns = [_mm256_setzero_si256(); 2 * LIMBS];
+        for i in 0..LIMBS {
+            for j in 0..LIMBS {
+                let prod = _mm256_mul_epu32(a[i], b[j]);
+                columns[i + j] =
+                    _mm256_add_epi64(columns[i + j], _mm256_and_si256(prod, low_mask()));
+                columns[i + j + 1] =
+                    _mm256_add_epi64(columns[i + j + 1], _mm256_srli_epi64(prod, 32));
+            }
+        }
+        propagate(&mut columns);
+        // Fold the upper half using 2^384 = MODULUS_COMPLEMENT mod MODULUS.
+        let complement = _mm256_set1_epi64x(MODULUS_COMPLEMENT as i64);
+        let mut folded = [_mm256_setzero_si256(); LIMBS];
+        for k in 0..LIMBS {
+            folded[k] =
+                _mm256_add_epi64(columns[k], _mm256_mul_epu32(columns[LIMBS + k], complement));
+        }
+        let top = propagate(&mut folded);
+        // The top is below 2^22, so folding it once more leaves a carry of at most one.
+        folded[0] = _mm256_add_epi64(folded[0], _mm256_mul_epu32(top, complement));
+        let carry = propagate(&mut folded);
+        reduce_once(&folded, carry)
+    }
+}
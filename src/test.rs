@@ -1,13 +1,59 @@
+use crate::batch::*;
+use crate::bfv::*;
+use crate::bgv::*;
+use crate::ckks::*;
+use crate::error::Error;
+use crate::modulus::*;
+use crate::noise::*;
+use crate::packing::*;
+#[cfg(feature = "parallel")]
+use crate::parallel::*;
 use crate::poly::*;
 use crate::residue::*;
+use crate::rgsw::*;
+use crate::rlwe::*;
+use crate::simd;
+#[cfg(feature = "noise-tracking")]
+use crate::tracked::*;
+use crate::transpose::*;
+use proptest::prelude::*;
+use rand::rngs::StdRng;
 use rand::thread_rng;
 use rand::Rng;
-use std::iter::{once, repeat};
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::iter::{once, repeat, successors};
+use zeroize::Zeroize;
 
 fn random_residues<'a, R: Rng>(rng: &'a mut R) -> impl Iterator<Item = Residue> + 'a {
     repeat(()).map(move |_| rng.gen())
 }
 
+// Whether the residue has absolute value at most 2^bits in centered representation
+fn is_small(residue: Residue, bits: usize) -> bool {
+    let bound = U384::one() << bits;
+    residue.0 <= bound || (-residue).0 <= bound
+}
+
+// Naive O(n^2) negacyclic product, skipping zero coefficients of a
+fn naive_mul(a: &PowerPoly, b: &PowerPoly) -> PowerPoly {
+    let mut result = PowerPoly(Box::new([Residue::zero(); DEGREE]));
+    for (i, &a_i) in a.0.iter().enumerate() {
+        if a_i == Residue::zero() {
+            continue;
+        }
+        for (j, &b_j) in b.0.iter().enumerate() {
+            if i + j < DEGREE {
+                result.0[i + j] += a_i * b_j;
+            } else {
+                result.0[i + j - DEGREE] -= a_i * b_j;
+            }
+        }
+    }
+    result
+}
+
 fn example_residues<'a, R: Rng>(rng: &'a mut R) -> impl Iterator<Item = Residue> + 'a {
     once(Residue::zero())
         .chain(once(Residue::one()))
@@ -49,6 +95,249 @@ fn test_residue_commutative_mult() {
     }
 }
 
+// The canonical representative of x, by U768 division as before the folding reduction
+fn reduce_by_division(x: U768) -> Residue {
+    let remainder = x % U768::from(MODULUS);
+    let mut limbs = [0; 6];
+    limbs.copy_from_slice(&remainder.0[..6]);
+    Residue(U384(limbs))
+}
+
+#[test]
+fn test_residue_against_division() {
+    let edge = [
+        Residue::zero(),
+        Residue::one(),
+        Residue(MODULUS - U384::one()),
+        Residue(U384::one() << 383),
+        // 2^384 - MODULUS
+        Residue(U384::max_value() - MODULUS + U384::one()),
+    ];
+    let modulus = U768::from(MODULUS);
+    for &a in edge.iter() {
+        let x = U768::from(a.0);
+        for &b in edge.iter() {
+            let y = U768::from(b.0);
+            assert_eq!(a * b, reduce_by_division(x * y));
+            assert_eq!(a + b, reduce_by_division(x + y));
+            assert_eq!(a - b, reduce_by_division(x + modulus - y));
+        }
+        assert_eq!(-a, reduce_by_division(modulus - x));
+    }
+}
+
+#[test]
+fn test_residue_inv() {
+    for elem in example_residues(&mut thread_rng()).skip(1).take(10) {
+        assert_eq!(elem * elem.inv(), Residue::one());
+    }
+}
+
+#[test]
+fn test_accumulator() {
+    let rng = &mut thread_rng();
+    let max = -Residue::one();
+    let mut pairs: Vec<(Residue, Residue)> = vec![(max, max); 1000];
+    pairs.extend((0..100).map(|_| (rng.gen(), rng.gen())));
+    let mut sum = Accumulator::zero();
+    let mut expected = Residue::zero();
+    for &(a, b) in pairs.iter() {
+        sum.mul_acc(a, b);
+        sum.acc(a);
+        expected += a * b + a;
+    }
+    assert_eq!(sum.reduce(), expected);
+    assert_eq!(Accumulator::zero().reduce(), Residue::zero());
+}
+
+#[test]
+fn test_inner_product() {
+    let mut rng = thread_rng();
+    let lhs: Vec<ChremPoly> = (0..3).map(|_| rng.gen()).collect();
+    let rhs: Vec<ChremPoly> = (0..3).map(|_| rng.gen()).collect();
+    let expected: ChremPoly = lhs.iter().zip(rhs.iter()).map(|(a, b)| a.clone() * b).sum();
+    assert_eq!(
+        ChremPoly::inner_product(lhs.iter().zip(rhs.iter())),
+        expected
+    );
+}
+
+// Edge values for the limb arithmetic, followed by random residues
+fn simd_operands<R: Rng>(rng: &mut R, len: usize) -> Vec<Residue> {
+    let q_minus = |x: u64| Residue(MODULUS - U384::from(x));
+    let edges = [
+        Residue::zero(),
+        Residue::one(),
+        q_minus(1),
+        q_minus(2),
+        Residue::from_u64(MODULUS_COMPLEMENT),
+        Residue(U384::one() << 383),
+    ];
+    let mut result: Vec<Residue> = edges
+        .iter()
+        .cycle()
+        .take(3 * edges.len())
+        .cloned()
+        .collect();
+    result.extend(random_residues(rng).take(len - result.len()));
+    result
+}
+
+#[test]
+fn test_simd_matches_scalar() {
+    let rng = &mut thread_rng();
+    // Not a multiple of the vector width, to exercise the remainder
+    let a = simd_operands(rng, 103);
+    let mut b = simd_operands(rng, 103);
+    b.reverse();
+    type Op = fn(&mut [Residue], &[Residue]);
+    let ops: [(Op, Op); 3] = [
+        (simd::add_assign, simd::scalar::add_assign),
+        (simd::sub_assign, simd::scalar::sub_assign),
+        (simd::mul_assign, simd::scalar::mul_assign),
+    ];
+    for &(op, scalar_op) in ops.iter() {
+        let mut expected = a.clone();
+        scalar_op(&mut expected, &b);
+        let mut actual = a.clone();
+        op(&mut actual, &b);
+        assert_eq!(actual, expected);
+    }
+    #[cfg(target_arch = "x86_64")]
+    {
+        if simd::avx2::is_available() {
+            // SAFETY: AVX2 is available, and the slices below have equal lengths.
+            let ops: [(Op, Op); 3] = [
+                (
+                    |a, b| unsafe { simd::avx2::add_assign(a, b) },
+                    simd::scalar::add_assign,
+                ),
+                (
+                    |a, b| unsafe { simd::avx2::sub_assign(a, b) },
+                    simd::scalar::sub_assign,
+                ),
+                (
+                    |a, b| unsafe { simd::avx2::mul_assign(a, b) },
+                    simd::scalar::mul_assign,
+                ),
+            ];
+            for &(op, scalar_op) in ops.iter() {
+                let mut expected = a.clone();
+                scalar_op(&mut expected, &b);
+                let mut actual = a.clone();
+                op(&mut actual, &b);
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_centered() {
+    let half = MODULUS >> 1;
+    for &x in &[0i64, 1, -1, 42, -42, i64::MAX, i64::MIN + 1] {
+        let centered = Residue::from_i64(x).to_centered();
+        assert_eq!(centered.to_i128(), Some(x as i128));
+        assert_eq!(Residue::from(centered), Residue::from_i64(x));
+    }
+    assert_eq!(
+        Residue(half).to_centered(),
+        Centered {
+            negative: false,
+            magnitude: half
+        }
+    );
+    assert_eq!(
+        Residue(half + 1).to_centered(),
+        Centered {
+            negative: true,
+            magnitude: half
+        }
+    );
+    assert_eq!(Residue(half).to_centered().to_i128(), None);
+}
+
+#[test]
+fn test_norms() {
+    let poly = PowerPoly::from_coeffs(&[
+        Residue::from_i64(3),
+        Residue::from_i64(-4),
+        Residue::zero(),
+        Residue::from_i64(-2),
+    ])
+    .unwrap();
+    assert_eq!(poly.linf_norm(), U384::from(4));
+    assert_eq!(poly.l2_norm_squared(), U832::from(29));
+    assert_eq!(PowerPoly::zero().linf_norm(), U384::zero());
+    // The largest norm doesn't overflow.
+    let half = Residue(MODULUS >> 1);
+    let poly = PowerPoly::from_coeffs(&vec![half; DEGREE]).unwrap();
+    let square = U768::from(half.0) * U768::from(half.0);
+    assert_eq!(poly.linf_norm(), half.0);
+    assert_eq!(
+        poly.l2_norm_squared(),
+        U832::from(square) * U832::from(DEGREE)
+    );
+}
+
+struct Q60;
+
+impl Modulus for Q60 {
+    const VALUE: u64 = (1 << 60) - 93;
+}
+
+struct Q17;
+
+impl Modulus for Q17 {
+    const VALUE: u64 = 65537;
+}
+
+// |to_coeff * from - from_coeff * to| <= from / 2 for centered coefficients
+fn check_rounding(from_coeff: Centered, from: U384, to_coeff: i64, to: u64) {
+    let scaled_from = U768::from(from_coeff.magnitude) * U768::from(to);
+    let scaled_to = U768::from(from) * U768::from(to_coeff.unsigned_abs());
+    let distance = if from_coeff.negative == (to_coeff < 0) || to_coeff == 0 {
+        if scaled_from > scaled_to {
+            scaled_from - scaled_to
+        } else {
+            scaled_to - scaled_from
+        }
+    } else {
+        scaled_from + scaled_to
+    };
+    assert!(distance <= U768::from(from >> 1));
+}
+
+#[test]
+fn test_mod_switch() {
+    let mut rng = thread_rng();
+    let mut poly: PowerPoly = rng.gen();
+    poly[0] = Residue::zero();
+    poly[1] = Residue(MODULUS >> 1);
+    poly[2] = -Residue(MODULUS >> 1);
+    poly[3] = Residue::from_i64(-1);
+    let switched = poly.mod_switch::<Q60>();
+    for (coeff, switched) in poly.iter().zip(switched.centered()) {
+        check_rounding(coeff.to_centered(), MODULUS, switched, Q60::VALUE);
+    }
+    assert_eq!(switched.coeffs()[3], 0);
+    let switched_again = switched.mod_switch::<Q17>();
+    for (coeff, switched) in switched.centered().zip(switched_again.centered()) {
+        let from = Residue::from_i64(coeff).to_centered();
+        check_rounding(from, U384::from(Q60::VALUE), switched, Q17::VALUE);
+    }
+    assert!(switched_again.linf_norm() <= Q17::VALUE / 2);
+    // Switching to the same modulus is the identity.
+    assert_eq!(switched.mod_switch::<Q60>(), switched);
+    let small = ModPoly::<Q17>::from_coeffs(&[1, 65536]).unwrap();
+    assert_eq!(small.clone() - &small, ModPoly::zero());
+    assert_eq!(-small.clone() + &small, ModPoly::zero());
+    assert_eq!(
+        ModPoly::<Q17>::from_coeffs(&[65537]),
+        Err(Error::OutOfRange)
+    );
+}
+
 #[test]
 fn test_index_th_root_order() {
     assert_eq!(INDEX_TH_ROOT.pow(DEGREE as u32), -Residue::one());
@@ -95,3 +384,981 @@ fn test_inv_crt_roundtrip() {
         assert_eq!(c1, c2);
     }
 }
+
+#[test]
+fn test_in_place_conversions() {
+    let mut rng = thread_rng();
+    let mut scratch = Scratch::new();
+    for _ in 0..3 {
+        let p: PowerPoly = rng.gen();
+        let c = p.clone().into_crt_in_place(&mut scratch);
+        assert_eq!(c, ChremPoly::from(p.clone()));
+        assert_eq!(c.into_power_in_place(&mut scratch), p);
+    }
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_matches_serial() {
+    let mut rng = thread_rng();
+    let p: PowerPoly = rng.gen();
+    let c: ChremPoly = rng.gen();
+    // Transforms, which split both their pointwise stages and their recursion
+    let mut results = Vec::new();
+    for &schedule in &[Schedule::Serial, Schedule::Parallel] {
+        let mut buffer = vec![Residue::zero(); DEGREE];
+        let mut slots = p.0.to_vec();
+        crt(
+            INDEX_BASE,
+            INDEX_POWER,
+            roots_of_unity(),
+            &mut slots,
+            &mut buffer,
+            schedule,
+        );
+        let mut coefficients = c.0.to_vec();
+        inv_crt(
+            INDEX_BASE,
+            INDEX_POWER,
+            roots_of_unity(),
+            &mut coefficients,
+            &mut buffer,
+            schedule,
+        );
+        results.push((slots, coefficients));
+    }
+    assert_eq!(results[0], results[1]);
+    assert_eq!(ChremPoly::from(p.clone()).0.as_ref(), &results[0].0[..]);
+    // Slot-wise operations
+    let mut products = Vec::new();
+    for &schedule in &[Schedule::Serial, Schedule::Parallel] {
+        let mut product = c.0.to_vec();
+        zip_apply(schedule, &mut product, p.0.as_ref(), simd::mul_assign);
+        products.push(product);
+    }
+    assert_eq!(products[0], products[1]);
+    // Inner products accumulate each chunk of slots separately.
+    let crt = ChremPoly::from(p);
+    let inner_product = ChremPoly::inner_product(vec![(&c, &crt), (&crt, &crt)]);
+    for i in 0..DEGREE {
+        assert_eq!(inner_product.0[i], c.0[i] * crt.0[i] + crt.0[i] * crt.0[i]);
+    }
+    // Transposition splits its blocks between threads.
+    let src: Vec<usize> = (0..128 * 256).collect();
+    let mut dest = vec![0; src.len()];
+    transpose(128, 256, &src, &mut dest).unwrap();
+    for i in 0..128 {
+        for j in 0..256 {
+            assert_eq!(dest[j * 128 + i], src[i * 256 + j]);
+        }
+    }
+}
+
+#[test]
+fn test_crt_monomial_product() {
+    let monomial = |exponent: usize| {
+        let mut p = PowerPoly(Box::new([Residue::zero(); DEGREE]));
+        p.0[exponent] = Residue::one();
+        ChremPoly::from(p)
+    };
+    for &(i, j) in &[
+        (1, 1),
+        (3, 5),
+        (DEGREE - 1, 1),
+        (DEGREE / 2, DEGREE / 2 + 7),
+    ] {
+        let mut c = monomial(i);
+        c *= &monomial(j);
+        let mut expected = PowerPoly(Box::new([Residue::zero(); DEGREE]));
+        expected.0[0] = Residue::one();
+        assert_eq!(PowerPoly::from(c), expected.mul_monomial(i + j));
+    }
+}
+
+#[test]
+fn test_power_poly_mul() {
+    let mut rng = thread_rng();
+    for _ in 0..3 {
+        let mut sparse = PowerPoly(Box::new([Residue::zero(); DEGREE]));
+        for _ in 0..8 {
+            sparse.0[rng.gen_range(0, DEGREE)] = rng.gen();
+        }
+        sparse.0[DEGREE - 1] = rng.gen();
+        let dense: PowerPoly = rng.gen();
+        let expected = naive_mul(&sparse, &dense);
+        assert_eq!(sparse.clone() * &dense, expected);
+        assert_eq!(dense * &sparse, expected);
+    }
+}
+
+#[test]
+fn test_monomials() {
+    for &exponent in &[0, 1, 5, DEGREE - 1, DEGREE, 2 * DEGREE - 1, 2 * DEGREE + 3] {
+        assert_eq!(
+            ChremPoly::from(PowerPoly::monomial(exponent)),
+            ChremPoly::monomial(exponent)
+        );
+    }
+    assert_eq!(PowerPoly::monomial(DEGREE), -PowerPoly::one());
+    assert_eq!(ChremPoly::monomial(0), ChremPoly::one());
+}
+
+#[test]
+fn test_poly_operators() {
+    let mut rng = thread_rng();
+    let polys: Vec<PowerPoly> = (0..3).map(|_| rng.gen()).collect();
+    let scalar: Residue = rng.gen();
+    let sum: PowerPoly = polys.iter().sum();
+    assert_eq!(sum, polys[0].clone() + &polys[1] + &polys[2]);
+    assert_eq!(sum.clone() - &polys[2] - &polys[1], polys[0]);
+    let product: PowerPoly = polys.iter().product();
+    assert_eq!(product, polys[0].clone() * &polys[1] * &polys[2]);
+    assert_eq!(polys[0].clone() * &PowerPoly::one(), polys[0]);
+    assert_eq!(
+        ChremPoly::from(scalar * polys[0].clone()),
+        ChremPoly::from(polys[0].clone()) * scalar
+    );
+    let chrems: Vec<ChremPoly> = polys.into_iter().map(ChremPoly::from).collect();
+    assert_eq!(ChremPoly::from(sum), chrems.iter().sum());
+    assert_eq!(ChremPoly::from(product), chrems.into_iter().product());
+    let mut p = PowerPoly::from_coeffs(&[scalar, Residue::one()]).unwrap();
+    assert_eq!(p[0], scalar);
+    p[0] = Residue::zero();
+    assert_eq!(p, PowerPoly::monomial(1));
+}
+
+#[test]
+fn test_eval() {
+    let mut rng = thread_rng();
+    let x: Residue = rng.gen();
+    let coeffs: Vec<Residue> = random_residues(&mut rng).take(3).collect();
+    let p = PowerPoly::from_coeffs(&coeffs).unwrap();
+    assert_eq!(p.eval(x), coeffs[0] + coeffs[1] * x + coeffs[2] * x * x);
+    assert_eq!(
+        p.eval_many(&[Residue::zero(), x]),
+        vec![coeffs[0], p.eval(x)]
+    );
+    assert_eq!(
+        PowerPoly::monomial(DEGREE - 1).eval(INDEX_TH_ROOT),
+        INV_INDEX_TH_ROOT * -Residue::one()
+    );
+}
+
+#[test]
+fn test_crt_slots_are_evaluations() {
+    let mut rng = thread_rng();
+    let p: PowerPoly = rng.gen();
+    let c = ChremPoly::from(p.clone());
+    let points = ChremPoly::slot_points();
+    let indices: Vec<usize> = (0..4)
+        .chain(DEGREE - 4..DEGREE)
+        .chain((0..8).map(|_| rng.gen_range(0, DEGREE)))
+        .collect();
+    let values = p.eval_many(&indices.iter().map(|&i| points[i]).collect::<Vec<_>>());
+    for (&i, value) in indices.iter().zip(values) {
+        assert_eq!(ChremPoly::slot_point(i), Ok(points[i]));
+        assert_eq!(c[i], value);
+    }
+    assert_eq!(ChremPoly::slot_point(DEGREE), Err(Error::OutOfRange));
+}
+
+#[test]
+fn test_decoding_powerful_roundtrip() {
+    let mut rng = thread_rng();
+    let p: PowerPoly = rng.gen();
+    let decoding = DecodingPoly::from(p.clone());
+    let powerful = PowerfulPoly::from(decoding.clone());
+    assert_eq!(PowerPoly::from(powerful.clone()), p);
+    assert_eq!(DecodingPoly::from(powerful), decoding);
+    // For power-of-two index, all three bases coincide.
+    assert!(decoding.iter().eq(p.iter()));
+}
+
+#[test]
+fn test_lwe_rlwe_phases() {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let message: PowerPoly = rng.gen();
+    let rlwe = RlweCiphertext::encrypt(&key, &message, &mut rng);
+    let phase = rlwe.phase(&key);
+    for &index in &[0, 1, DEGREE / 2, DEGREE - 1] {
+        let lwe = rlwe.sample_extract(index);
+        assert_eq!(lwe.phase(&key), phase.0[index]);
+        assert_eq!(RlweCiphertext::from(&lwe).phase(&key).0[0], phase.0[index]);
+    }
+}
+
+#[test]
+fn test_key_switching() {
+    let mut rng = thread_rng();
+    let from = SecretKey::generate(&mut rng);
+    let to = SecretKey::generate(&mut rng);
+    let key = KeySwitchingKey::generate(&from, &to, &mut rng);
+    let message: PowerPoly = rng.gen();
+    let ciphertext = RlweCiphertext::encrypt(&from, &message, &mut rng);
+    let mut noise = key.switch(&ciphertext).phase(&to);
+    noise -= &message;
+    assert!(noise.0.iter().all(|&r| is_small(r, 200)));
+}
+
+#[test]
+fn test_trace() {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let packing_keys = PackingKeys::generate(&key, &mut rng);
+    let message: PowerPoly = rng.gen();
+    let ciphertext = RlweCiphertext::encrypt(&key, &message, &mut rng);
+    let mut noise = packing_keys.trace(ciphertext).phase(&key);
+    noise.0[0] -= Residue::from_u64(DEGREE as u64) * message.0[0];
+    assert!(noise.0.iter().all(|&r| is_small(r, 200)));
+}
+
+#[test]
+fn test_pack_lwes() {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let packing_keys = PackingKeys::generate(&key, &mut rng);
+    let messages: Vec<Residue> = random_residues(&mut rng).take(3).collect();
+    let lwes: Vec<LweCiphertext> = messages
+        .iter()
+        .map(|&m| LweCiphertext::encrypt(&key, m, &mut rng))
+        .collect();
+    let mut noise = packing_keys.pack_lwes(&lwes).unwrap().phase(&key);
+    for (j, &m) in messages.iter().enumerate() {
+        noise.0[j * DEGREE / 4] -= m;
+    }
+    assert!(noise.0.iter().all(|&r| is_small(r, 200)));
+}
+
+#[test]
+fn test_plaintext() {
+    let plaintext = Plaintext::new(17, &[16, 0, 3]).unwrap();
+    assert_eq!(plaintext.coeffs().len(), DEGREE);
+    assert_eq!(Plaintext::decode(&plaintext.encode(), 17), Ok(plaintext));
+    assert_eq!(
+        Plaintext::new(1, &[]),
+        Err(Error::ParameterMismatch(
+            "the plaintext modulus must be at least 2"
+        ))
+    );
+    assert_eq!(Plaintext::new(17, &[17]), Err(Error::OutOfRange));
+    assert!(Plaintext::new(17, &vec![0; DEGREE + 1]).is_err());
+}
+
+fn random_plaintext<R: Rng>(rng: &mut R, modulus: u64) -> Plaintext {
+    let coeffs: Vec<u64> = (0..DEGREE).map(|_| rng.gen_range(0, modulus)).collect();
+    Plaintext::new(modulus, &coeffs).unwrap()
+}
+
+#[test]
+fn test_external_product() {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let plaintext = random_plaintext(&mut rng, 1 << 16);
+    let ciphertext = RlweCiphertext::encrypt(&key, &plaintext.encode(), &mut rng);
+    let rgsw = RgswCiphertext::encrypt(&key, &PowerPoly::monomial(3), &mut rng);
+    let product = rgsw.external_product(&ciphertext);
+    let decrypted = Plaintext::decode(&product.phase(&key), 1 << 16).unwrap();
+    let expected = Plaintext::decode(&plaintext.encode().mul_monomial(3), 1 << 16).unwrap();
+    assert_eq!(decrypted, expected);
+}
+
+#[test]
+fn test_cmux() {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let plaintexts = [
+        random_plaintext(&mut rng, 256),
+        random_plaintext(&mut rng, 256),
+    ];
+    let ciphertexts: Vec<_> = plaintexts
+        .iter()
+        .map(|p| RlweCiphertext::encrypt(&key, &p.encode(), &mut rng))
+        .collect();
+    for (bit, plaintext) in plaintexts.iter().enumerate() {
+        let message = PowerPoly::from_coeffs(&[Residue::from_u64(bit as u64)]).unwrap();
+        let selector = RgswCiphertext::encrypt(&key, &message, &mut rng);
+        let result = selector.cmux(&ciphertexts[0], &ciphertexts[1]);
+        let decrypted = Plaintext::decode(&result.phase(&key), 256).unwrap();
+        assert_eq!(&decrypted, plaintext);
+    }
+}
+
+#[test]
+fn test_decrypt_with_noise() {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let plaintext = random_plaintext(&mut rng, 1 << 16);
+    let ciphertext = RlweCiphertext::encrypt(&key, &plaintext.encode(), &mut rng);
+    let (decrypted, noise) = decrypt_with_noise(&key, &ciphertext, 1 << 16).unwrap();
+    assert_eq!(decrypted, plaintext);
+    assert!(noise.linf_norm <= U384::from(20));
+    assert!(noise.budget(1 << 16) > 360);
+    let trivial = RlweCiphertext::trivial(plaintext.encode());
+    let (_, noise) = decrypt_with_noise(&key, &trivial, 1 << 16).unwrap();
+    assert_eq!(noise.bits(), 0);
+}
+
+#[cfg(feature = "noise-tracking")]
+#[test]
+fn test_external_product_noise_estimate() {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let plaintext = random_plaintext(&mut rng, 1 << 16);
+    let ciphertext = Tracked::<RlweCiphertext>::encrypt(&key, &plaintext.encode(), &mut rng);
+    let rgsw = Tracked::<RgswCiphertext>::encrypt(&key, &PowerPoly::monomial(3), &mut rng);
+    let product = rgsw.external_product(&ciphertext);
+    let (decrypted, noise) = decrypt_with_noise(&key, &product.value, 1 << 16).unwrap();
+    let expected = Plaintext::decode(&plaintext.encode().mul_monomial(3), 1 << 16).unwrap();
+    assert_eq!(decrypted, expected);
+    // The maximum of DEGREE Gaussians is a few standard deviations.
+    let excess = noise.log2() - product.log2_stddev();
+    assert!(0.0 < excess && excess < 4.0, "excess {}", excess);
+}
+
+#[cfg(feature = "noise-tracking")]
+#[test]
+fn test_cmux_noise_estimate() {
+    let mut rng = thread_rng();
+    let key = SecretKey::generate(&mut rng);
+    let plaintexts = [
+        random_plaintext(&mut rng, 256),
+        random_plaintext(&mut rng, 256),
+    ];
+    let ciphertexts: Vec<_> = plaintexts
+        .iter()
+        .map(|p| Tracked::<RlweCiphertext>::encrypt(&key, &p.encode(), &mut rng))
+        .collect();
+    for (bit, plaintext) in plaintexts.iter().enumerate() {
+        let message = PowerPoly::from_coeffs(&[Residue::from_u64(bit as u64)]).unwrap();
+        let selector = Tracked::<RgswCiphertext>::encrypt(&key, &message, &mut rng);
+        let result = selector.cmux(&ciphertexts[0], &ciphertexts[1]);
+        let (decrypted, noise) = decrypt_with_noise(&key, &result.value, 256).unwrap();
+        assert_eq!(&decrypted, plaintext);
+        assert!(noise.log2() < result.log2_stddev() + 4.0);
+    }
+}
+
+#[cfg(feature = "noise-tracking")]
+#[test]
+fn test_key_switch_noise_estimate() {
+    let mut rng = thread_rng();
+    let from = SecretKey::generate(&mut rng);
+    let to = SecretKey::generate(&mut rng);
+    let key = KeySwitchingKey::generate(&from, &to, &mut rng);
+    let plaintext = random_plaintext(&mut rng, 1 << 16);
+    let ciphertext = Tracked::<RlweCiphertext>::encrypt(&from, &plaintext.encode(), &mut rng);
+    let switched = ciphertext.key_switch(&key);
+    let (decrypted, noise) = decrypt_with_noise(&to, &switched.value, 1 << 16).unwrap();
+    assert_eq!(decrypted, plaintext);
+    let excess = noise.log2() - switched.log2_stddev();
+    assert!(0.0 < excess && excess < 4.0, "excess {}", excess);
+}
+
+// Negacyclic product modulo t, with the second factor given by its nonzero coefficients
+fn sparse_mul_mod(a: &[u64], b: &[(usize, u64)], t: u64) -> Vec<u64> {
+    let mut result = vec![0; DEGREE];
+    for &(j, coeff) in b {
+        for (i, &x) in a.iter().enumerate() {
+            let product = (x as u128 * coeff as u128 % t as u128) as u64;
+            let (k, product) = if i + j < DEGREE {
+                (i + j, product)
+            } else {
+                (i + j - DEGREE, (t - product) % t)
+            };
+            result[k] = (result[k] + product) % t;
+        }
+    }
+    result
+}
+
+#[test]
+fn test_bfv() {
+    let mut rng = thread_rng();
+    let t = 65537;
+    let bfv = Bfv::new(t).unwrap();
+    let key = SecretKey::generate(&mut rng);
+    let relinearization_key = RelinearizationKey::generate(&key, &mut rng);
+    let m0 = random_plaintext(&mut rng, t);
+    let sparse: Vec<(usize, u64)> = (0..3)
+        .map(|_| (rng.gen_range(0, DEGREE), rng.gen_range(0, t)))
+        .collect();
+    let m1 = Plaintext::new(t, &sparse_mul_mod(&[1], &sparse, t)).unwrap();
+    let c0 = bfv.encrypt(&key, &m0, &mut rng).unwrap();
+    let c1 = bfv.encrypt(&key, &m1, &mut rng).unwrap();
+    let sum: Vec<u64> = m0
+        .coeffs()
+        .iter()
+        .zip(m1.coeffs())
+        .map(|(x, y)| (x + y) % t)
+        .collect();
+    assert_eq!(
+        bfv.decrypt(&key, &bfv.add(&c0, &c1)),
+        Plaintext::new(t, &sum).unwrap()
+    );
+    assert_eq!(
+        bfv.decrypt(&key, &bfv.add_plain(&c0, &m1).unwrap()),
+        Plaintext::new(t, &sum).unwrap()
+    );
+    let product = bfv.mul(&c0, &c1, &relinearization_key);
+    let expected = sparse_mul_mod(m0.coeffs(), &sparse, t);
+    assert_eq!(bfv.decrypt(&key, &product).coeffs(), &expected[..]);
+    // The relinearized product can be multiplied again.
+    let square = bfv.mul(&c1, &c1, &relinearization_key);
+    let product = bfv.mul(&product, &square, &relinearization_key);
+    let expected = sparse_mul_mod(&sparse_mul_mod(&expected, &sparse, t), &sparse, t);
+    assert_eq!(bfv.decrypt(&key, &product).coeffs(), &expected[..]);
+    let (_, noise) = decrypt_with_noise(&key, &product, t).unwrap();
+    assert!(noise.budget(t) > 150);
+    assert!(bfv
+        .encrypt(&key, &random_plaintext(&mut rng, 257), &mut rng)
+        .is_err());
+    assert!(Bfv::new(1).is_err());
+}
+
+// A chain of two moduli which are congruent to 1 modulo 257
+struct Q62;
+
+impl Modulus for Q62 {
+    const VALUE: u64 = (1 << 62) / 257 * 257 + 1;
+}
+
+struct Q40;
+
+impl Modulus for Q40 {
+    const VALUE: u64 = (1 << 40) / 257 * 257 + 1;
+}
+
+#[test]
+fn test_bgv() {
+    let mut rng = thread_rng();
+    let t = 257;
+    let bgv = Bgv::new(t).unwrap();
+    let key = SecretKey::generate(&mut rng);
+    let relinearization_key = bgv.relinearization_key::<Q62, _>(&key, &mut rng).unwrap();
+    let m0 = random_plaintext(&mut rng, t);
+    let sparse: Vec<(usize, u64)> = (0..3)
+        .map(|_| (rng.gen_range(0, DEGREE), rng.gen_range(0, t)))
+        .collect();
+    let m1 = Plaintext::new(t, &sparse_mul_mod(&[1], &sparse, t)).unwrap();
+    let c0: BgvCiphertext<Q62> = bgv.encrypt(&key, &m0, &mut rng).unwrap();
+    let c1: BgvCiphertext<Q62> = bgv.encrypt(&key, &m1, &mut rng).unwrap();
+    assert_eq!(bgv.decrypt(&key, &c0), m0);
+    let sum: Vec<u64> = m0
+        .coeffs()
+        .iter()
+        .zip(m1.coeffs())
+        .map(|(x, y)| (x + y) % t)
+        .collect();
+    assert_eq!(bgv.decrypt(&key, &bgv.add(&c0, &c1)).coeffs(), &sum[..]);
+    let product = bgv.mul(&c0, &c1, &relinearization_key);
+    let expected = sparse_mul_mod(m0.coeffs(), &sparse, t);
+    assert_eq!(bgv.decrypt(&key, &product).coeffs(), &expected[..]);
+    // Switching down the chain preserves the message and shrinks the noise.
+    let switched = bgv.mod_switch::<Q62, Q40>(&product).unwrap();
+    assert_eq!(bgv.decrypt(&key, &switched).coeffs(), &expected[..]);
+    let phase = switched.b.clone() - &(switched.a.clone() * &ModPoly::reduce(key.power()));
+    assert!(phase.linf_norm() < Q40::VALUE / 1024);
+    // Key switching
+    let other_key = SecretKey::generate(&mut rng);
+    let switching_key = bgv
+        .key_switching_key::<Q40, _>(&key, &other_key, &mut rng)
+        .unwrap();
+    let switched = bgv.key_switch(&switched, &switching_key);
+    assert_eq!(bgv.decrypt(&other_key, &switched).coeffs(), &expected[..]);
+    assert!(bgv.mod_switch::<Q62, Q60>(&c0).is_err());
+    assert!(bgv
+        .encrypt::<Q17, _>(&key, &random_plaintext(&mut rng, 65537), &mut rng)
+        .is_err());
+}
+
+fn random_complex<R: Rng>(rng: &mut R, len: usize) -> Vec<Complex> {
+    (0..len)
+        .map(|_| Complex::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0)))
+        .collect()
+}
+
+fn max_distance(a: &[Complex], b: &[Complex]) -> f64 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| (x - y).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn test_ckks_encoding() {
+    let mut rng = thread_rng();
+    let encoder = CkksEncoder::new();
+    let scale = 2f64.powi(40);
+    let values = random_complex(&mut rng, SLOTS);
+    let encoded = encoder.encode(&values, scale).unwrap();
+    assert!(max_distance(&encoder.decode(&encoded), &values) < 1e-9);
+    // The slots are the evaluations at ZETA^(5^j).
+    let coeffs: Vec<f64> = encoded
+        .poly
+        .iter()
+        .map(|&residue| match residue.to_centered().to_i128() {
+            Some(x) => x as f64 / scale,
+            None => panic!("coefficient too large"),
+        })
+        .collect();
+    for &j in &[0, 1, 2, 1000, SLOTS - 1] {
+        let exponent = (0..j).fold(1, |power, _| power * 5 % (2 * DEGREE));
+        let mut evaluation = Complex::default();
+        for (k, &coeff) in coeffs.iter().enumerate() {
+            let angle = std::f64::consts::PI * (exponent * k % (2 * DEGREE)) as f64 / DEGREE as f64;
+            evaluation = evaluation + Complex::new(coeff * angle.cos(), coeff * angle.sin());
+        }
+        assert!((evaluation - values[j]).abs() < 1e-6);
+    }
+    // Scales beyond 2^63 take the big integer path.
+    let encoded = encoder.encode(&values[..10], 2f64.powi(200)).unwrap();
+    let decoded = encoder.decode(&encoded);
+    assert!(max_distance(&decoded[..10], &values[..10]) < 1e-9);
+    assert!(decoded[10..].iter().all(|x| x.abs() < 1e-9));
+    assert_eq!(
+        encoder.encode(&values, 2f64.powi(400)),
+        Err(Error::OutOfRange)
+    );
+}
+
+#[test]
+fn test_ckks_precision() {
+    let mut rng = thread_rng();
+    let encoder = CkksEncoder::new();
+    let scale = 1 << 40;
+    let x = random_complex(&mut rng, SLOTS);
+    let y = random_complex(&mut rng, SLOTS);
+    let x_encoded = encoder.encode(&x, scale as f64).unwrap();
+    let y_encoded = encoder.encode(&y, scale as f64).unwrap();
+    // Slot-wise product of the plaintext encodings, as ciphertexts can't be multiplied here
+    let product = Encoded {
+        poly: x_encoded.poly.clone() * &y_encoded.poly,
+        scale: x_encoded.scale * y_encoded.scale,
+    };
+    let rescaled = encoder.rescale(&product, scale).unwrap();
+    assert_eq!(rescaled.scale, scale as f64);
+    let expected: Vec<Complex> = x.iter().zip(y.iter()).map(|(&a, &b)| a * b).collect();
+    assert!(max_distance(&encoder.decode(&rescaled), &expected) < 1e-6);
+    assert_eq!(
+        encoder.rescale(&product, 0),
+        Err(Error::ParameterMismatch("divisor is zero"))
+    );
+    // Encryption adds noise far below the scale.
+    let key = SecretKey::generate(&mut rng);
+    let mut ciphertext = RlweCiphertext::encrypt(&key, &x_encoded.poly, &mut rng);
+    ciphertext += &RlweCiphertext::encrypt(&key, &y_encoded.poly, &mut rng);
+    let decrypted = Encoded {
+        poly: ciphertext.phase(&key),
+        scale: scale as f64,
+    };
+    let expected: Vec<Complex> = x.iter().zip(y.iter()).map(|(&a, &b)| a + b).collect();
+    assert!(max_distance(&encoder.decode(&decrypted), &expected) < 1e-6);
+}
+
+#[test]
+fn test_ckks_rescaling() {
+    let mut rng = thread_rng();
+    let encoder = CkksEncoder::new();
+    let scale = 2f64.powi(25);
+    let key = SecretKey::generate(&mut rng);
+    let x = random_complex(&mut rng, SLOTS);
+    let y = random_complex(&mut rng, SLOTS);
+    let x_encoded = encoder.encode(&x, scale).unwrap();
+    let y_encoded = encoder.encode(&y, scale).unwrap();
+    let ciphertext: CkksCiphertext<Q62> =
+        CkksCiphertext::encrypt(&key, &x_encoded, &mut rng).unwrap();
+    assert!(max_distance(&encoder.decode(&ciphertext.decrypt(&key)), &x) < 1e-4);
+    // The product has scale 2^50, and rescaling brings it down to about 2^28.
+    let product = ciphertext.mul_plain(&y_encoded).unwrap();
+    let rescaled = product.rescale::<Q40>().unwrap();
+    assert_eq!(
+        rescaled.scale,
+        scale * scale * Q40::VALUE as f64 / Q62::VALUE as f64
+    );
+    let expected: Vec<Complex> = x.iter().zip(y.iter()).map(|(&a, &b)| a * b).collect();
+    assert!(max_distance(&encoder.decode(&rescaled.decrypt(&key)), &expected) < 1e-3);
+    let sum = ciphertext.add(&ciphertext).unwrap();
+    let doubled: Vec<Complex> = x.iter().map(|&a| a + a).collect();
+    assert!(max_distance(&encoder.decode(&sum.decrypt(&key)), &doubled) < 1e-4);
+    assert!(ciphertext.add(&product).is_err());
+    assert!(rescaled.rescale::<Q62>().is_err());
+    // Encodings that don't fit below Q40 / 2 are rejected.
+    let large = encoder.encode(&x, 2f64.powi(45)).unwrap();
+    assert!(CkksCiphertext::<Q40>::encrypt(&key, &large, &mut rng).is_err());
+}
+
+struct NotOneModIndex;
+
+impl Modulus for NotOneModIndex {
+    const VALUE: u64 = 65539;
+}
+
+// 3 * 10923, but 1 modulo 2 * DEGREE
+struct Composite;
+
+impl Modulus for Composite {
+    const VALUE: u64 = 32769;
+}
+
+#[test]
+fn test_batch_encoder() {
+    let mut rng = thread_rng();
+    let t = Q17::VALUE;
+    let encoder = BatchEncoder::<Q17>::new().unwrap();
+    let x: Vec<u64> = (0..DEGREE).map(|_| rng.gen_range(0, t)).collect();
+    let y: Vec<u64> = (0..DEGREE).map(|_| rng.gen_range(0, t)).collect();
+    let x_encoded = encoder.encode(&x).unwrap();
+    assert_eq!(encoder.decode(&x_encoded).unwrap(), x);
+    // Equal slots encode a constant.
+    let constant = encoder.encode(&vec![5; DEGREE]).unwrap();
+    assert_eq!(constant, Plaintext::new(t, &[5]).unwrap());
+    // Homomorphic operations act slot-wise.
+    let bfv = Bfv::new(t).unwrap();
+    let key = SecretKey::generate(&mut rng);
+    let relinearization_key = RelinearizationKey::generate(&key, &mut rng);
+    let x_encrypted = bfv.encrypt(&key, &x_encoded, &mut rng).unwrap();
+    let y_encrypted = bfv
+        .encrypt(&key, &encoder.encode(&y).unwrap(), &mut rng)
+        .unwrap();
+    let product = bfv.mul(&x_encrypted, &y_encrypted, &relinearization_key);
+    let expected: Vec<u64> = x.iter().zip(y.iter()).map(|(a, b)| a * b % t).collect();
+    assert_eq!(
+        encoder.decode(&bfv.decrypt(&key, &product)).unwrap(),
+        expected
+    );
+    let sum = bfv.add(&x_encrypted, &y_encrypted);
+    let expected: Vec<u64> = x.iter().zip(y.iter()).map(|(a, b)| (a + b) % t).collect();
+    assert_eq!(encoder.decode(&bfv.decrypt(&key, &sum)).unwrap(), expected);
+    assert!(BatchEncoder::<NotOneModIndex>::new().is_err());
+    assert!(BatchEncoder::<Composite>::new().is_err());
+    assert_eq!(encoder.encode(&[t]), Err(Error::OutOfRange));
+    assert!(encoder.decode(&Plaintext::new(257, &[1]).unwrap()).is_err());
+}
+
+#[test]
+fn test_transpose() {
+    for &(majors, minors) in &[
+        (1, 1),
+        (1, 7),
+        (7, 1),
+        (3, 5),
+        (17, 33),
+        (64, 64),
+        (2, 16384),
+    ] {
+        let src: Vec<usize> = (0..majors * minors).collect();
+        let mut dest = vec![0; majors * minors];
+        transpose(majors, minors, &src, &mut dest).unwrap();
+        for i in 0..majors {
+            for j in 0..minors {
+                assert_eq!(dest[j * majors + i], src[i * minors + j]);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_transpose_strided() {
+    let (majors, minors, src_stride, dest_stride) = (13, 29, 31, 20);
+    let src: Vec<usize> = (0..majors * src_stride).collect();
+    for &block_size in &[1, 4, 100, DEFAULT_BLOCK_SIZE, 10000] {
+        // The padding of dest must be left untouched.
+        let mut dest = vec![usize::MAX; minors * dest_stride];
+        transpose_strided(
+            majors,
+            minors,
+            &src,
+            src_stride,
+            &mut dest,
+            dest_stride,
+            block_size,
+        )
+        .unwrap();
+        for j in 0..minors {
+            for i in 0..dest_stride {
+                let expected = if i < majors {
+                    src[i * src_stride + j]
+                } else {
+                    usize::MAX
+                };
+                assert_eq!(dest[j * dest_stride + i], expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_transpose_in_place() {
+    for &(n, stride) in &[(0, 0), (1, 1), (5, 5), (33, 40), (100, 100)] {
+        for &block_size in &[1, 16, DEFAULT_BLOCK_SIZE] {
+            let original: Vec<usize> = (0..n * stride).collect();
+            let mut data = original.clone();
+            transpose_in_place(n, &mut data, stride, block_size).unwrap();
+            for i in 0..n {
+                for j in 0..stride {
+                    let expected = if j < n {
+                        original[j * stride + i]
+                    } else {
+                        original[i * stride + j]
+                    };
+                    assert_eq!(data[i * stride + j], expected);
+                }
+            }
+        }
+    }
+}
+
+// Residues for the property tests, biased towards edge values
+fn residue_strategy() -> impl Strategy<Value = Residue> {
+    prop_oneof![
+        1 => Just(Residue::zero()),
+        1 => Just(Residue::one()),
+        1 => Just(-Residue::one()),
+        1 => Just(-Residue::from_u64(2)),
+        1 => Just(Residue::from_u64(MODULUS_COMPLEMENT)),
+        6 => any::<[u64; 6]>().prop_map(|limbs| Residue(U384(limbs) % MODULUS)),
+    ]
+}
+
+// Polynomials with few nonzero coefficients, so that naive_mul stays cheap
+fn sparse_poly_strategy() -> impl Strategy<Value = PowerPoly> {
+    let index = prop_oneof![Just(0), Just(DEGREE - 1), 0..DEGREE];
+    prop::collection::vec((index, residue_strategy()), 0..6).prop_map(|terms| {
+        let mut result = PowerPoly::zero();
+        for (index, coeff) in terms {
+            result[index] = coeff;
+        }
+        result
+    })
+}
+
+// Uniformly random polynomials, generated from a seed to keep the strategy small
+fn dense_poly_strategy() -> impl Strategy<Value = PowerPoly> {
+    any::<u64>().prop_map(|seed| StdRng::seed_from_u64(seed).gen())
+}
+
+proptest! {
+    #[test]
+    fn prop_residue_add_associative(
+        a in residue_strategy(),
+        b in residue_strategy(),
+        c in residue_strategy(),
+    ) {
+        prop_assert_eq!((a + b) + c, a + (b + c));
+    }
+
+    #[test]
+    fn prop_residue_mul_associative(
+        a in residue_strategy(),
+        b in residue_strategy(),
+        c in residue_strategy(),
+    ) {
+        prop_assert_eq!((a * b) * c, a * (b * c));
+    }
+
+    #[test]
+    fn prop_residue_distributive(
+        a in residue_strategy(),
+        b in residue_strategy(),
+        c in residue_strategy(),
+    ) {
+        prop_assert_eq!(a * (b + c), a * b + a * c);
+        prop_assert_eq!(a * (b - c), a * b - a * c);
+    }
+
+    #[test]
+    fn prop_residue_additive_inverse(a in residue_strategy(), b in residue_strategy()) {
+        prop_assert_eq!(a + -a, Residue::zero());
+        prop_assert_eq!(a - b, a + -b);
+        prop_assert_eq!(a - b + b, a);
+    }
+
+    #[test]
+    fn prop_residue_multiplicative_inverse(a in residue_strategy()) {
+        prop_assume!(a != Residue::zero());
+        prop_assert_eq!(a * a.inv(), Residue::one());
+    }
+}
+
+proptest! {
+    // Each case converts several polynomials.
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn prop_crt_additive(a in dense_poly_strategy(), b in dense_poly_strategy()) {
+        let sum = ChremPoly::from(a.clone() + &b);
+        prop_assert_eq!(sum, ChremPoly::from(a) + &ChremPoly::from(b));
+    }
+
+    #[test]
+    fn prop_crt_multiplicative(
+        a in sparse_poly_strategy(),
+        b in prop_oneof![sparse_poly_strategy(), dense_poly_strategy()],
+    ) {
+        let product = ChremPoly::from(a.clone()) * &ChremPoly::from(b.clone());
+        prop_assert_eq!(PowerPoly::from(product), naive_mul(&a, &b));
+    }
+}
+
+// Known-answer vectors from an independent reference, see testdata/generate.py. Maps each
+// section name to its (index, value) pairs.
+fn known_answers() -> HashMap<String, Vec<(usize, Residue)>> {
+    let mut result = HashMap::new();
+    let mut section = None;
+    for line in include_str!("../testdata/crt.kat").lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            let name = line.trim_start_matches('[').trim_end_matches(']');
+            section = Some(name.to_string());
+            result.insert(name.to_string(), Vec::new());
+            continue;
+        }
+        let mut fields = line.split(' ');
+        let index = fields.next().unwrap().parse().unwrap();
+        let value = fields.next().unwrap().parse::<U384>().unwrap();
+        result
+            .get_mut(section.as_ref().unwrap())
+            .unwrap()
+            .push((index, Residue(value)));
+    }
+    result
+}
+
+fn powers_of(base: u64) -> Vec<Residue> {
+    let base = Residue::from_u64(base);
+    successors(Some(Residue::one()), |&power| Some(power * base))
+        .take(DEGREE)
+        .collect()
+}
+
+fn check_known_answers(values: &[Residue], expected: &[(usize, Residue)]) {
+    assert!(!expected.is_empty());
+    for &(index, value) in expected {
+        assert_eq!(values[index], value, "index {}", index);
+    }
+}
+
+#[test]
+fn test_crt_known_answers() {
+    let known_answers = known_answers();
+    let linear: Vec<Residue> = (0..DEGREE as u64).map(Residue::from_u64).collect();
+    let cases = [
+        ("power_to_crt powers_of_3", powers_of(3)),
+        ("power_to_crt linear", linear),
+        ("power_to_crt minus_one", vec![-Residue::one(); DEGREE]),
+    ];
+    for (name, coeffs) in cases.iter() {
+        let chrem = ChremPoly::from(PowerPoly::from_coeffs(coeffs).unwrap());
+        check_known_answers(chrem.0.as_ref(), &known_answers[*name]);
+    }
+    let power = PowerPoly::from(ChremPoly::from_slots(&powers_of(5)).unwrap());
+    check_known_answers(power.0.as_ref(), &known_answers["crt_to_power powers_of_5"]);
+}
+
+#[test]
+fn test_residue_bytes() {
+    for elem in example_residues(&mut thread_rng()).take(20) {
+        assert_eq!(Residue::from_bytes(&elem.to_bytes()), Ok(elem));
+    }
+    let q_minus_one = -Residue::one();
+    assert_eq!(
+        Residue::from_bytes(&q_minus_one.to_bytes()),
+        Ok(q_minus_one)
+    );
+    let mut bytes = q_minus_one.to_bytes();
+    bytes[0] += 1;
+    assert!(Residue::from_bytes(&bytes).is_err());
+    assert!(Residue::from_bytes(&[0xff; RESIDUE_BYTES]).is_err());
+    assert!(Residue::from_bytes(&[0; RESIDUE_BYTES - 1]).is_err());
+    assert!(Residue::from_bytes(&[0; RESIDUE_BYTES + 1]).is_err());
+}
+
+#[test]
+fn test_poly_bytes() {
+    let mut rng = thread_rng();
+    let power: PowerPoly = rng.gen();
+    let bytes = power.to_bytes();
+    assert_eq!(bytes.len(), POLY_BYTES);
+    assert_eq!(PowerPoly::from_bytes(&bytes), Ok(power));
+    let chrem: ChremPoly = rng.gen();
+    assert_eq!(ChremPoly::from_bytes(&chrem.to_bytes()), Ok(chrem));
+    assert!(PowerPoly::from_bytes(&bytes[1..]).is_err());
+    let mut unreduced = bytes;
+    unreduced[POLY_BYTES - RESIDUE_BYTES..].copy_from_slice(&[0xff; RESIDUE_BYTES]);
+    assert!(ChremPoly::from_bytes(&unreduced).is_err());
+
+    let key = SecretKey::generate(&mut rng);
+    let ciphertext = RlweCiphertext::encrypt(&key, &rng.gen(), &mut rng);
+    let bytes = ciphertext.to_bytes();
+    assert_eq!(RlweCiphertext::from_bytes(&bytes), Ok(ciphertext));
+    assert!(RlweCiphertext::from_bytes(&bytes[..POLY_BYTES]).is_err());
+}
+
+#[test]
+fn test_errors() {
+    let short = [Residue::one(); DEGREE - 1];
+    assert_eq!(
+        ChremPoly::from_slots(&short),
+        Err(Error::LengthMismatch {
+            expected: DEGREE,
+            actual: DEGREE - 1
+        })
+    );
+    let long = vec![Residue::one(); DEGREE + 1];
+    assert!(PowerPoly::from_coeffs(&long).is_err());
+    assert_eq!(Residue::try_from(MODULUS), Err(Error::OutOfRange));
+    assert_eq!(
+        Residue::try_from(MODULUS - U384::one()),
+        Ok(-Residue::one())
+    );
+    assert_eq!(
+        U384::try_from(U768::from(MODULUS) << 384),
+        Err(Error::OutOfRange)
+    );
+    assert!(matches!(
+        Residue::from_bytes(&[0; 3]),
+        Err(Error::Decode(_))
+    ));
+
+    let mut dest = [0; 6];
+    assert_eq!(
+        transpose(2, 3, &[0; 5], &mut dest),
+        Err(Error::LengthMismatch {
+            expected: 6,
+            actual: 5
+        })
+    );
+    assert!(matches!(
+        transpose_strided(2, 3, &[0; 6], 2, &mut dest, 2, 1),
+        Err(Error::ParameterMismatch(_))
+    ));
+    assert!(matches!(
+        transpose_in_place(2, &mut dest, 3, 0),
+        Err(Error::ParameterMismatch(_))
+    ));
+    assert_eq!(Error::OutOfRange.to_string(), "value out of range");
+}
+
+#[test]
+fn test_zeroize() {
+    let mut rng = thread_rng();
+    let mut power: PowerPoly = rng.gen();
+    power.zeroize();
+    assert_eq!(power, PowerPoly::zero());
+    let mut chrem: ChremPoly = rng.gen();
+    chrem.zeroize();
+    assert_eq!(chrem, ChremPoly::zero());
+    let mut residue: Residue = rng.gen();
+    residue.zeroize();
+    assert_eq!(residue, Residue::zero());
+    let mut sum = Accumulator::zero();
+    sum.mul_acc(rng.gen(), rng.gen());
+    sum.zeroize();
+    assert_eq!(sum.reduce(), Residue::zero());
+
+    let key = SecretKey::generate(&mut rng);
+    assert_eq!(format!("{:?}", key), "SecretKey(<redacted>)");
+}
@@ -0,0 +1,88 @@
+use crate::poly::*;
+use crate::residue::*;
+use crate::rgsw::*;
+use crate::rlwe::*;
+use rand::Rng;
+
+// A ciphertext with a heuristic estimate of the variance of its noise coefficients, for debugging
+// noise growth. The estimates treat all noise coefficients as independent and the gadget digits as
+// uniform in [0, 2^GADGET_LOG_BASE).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tracked<T> {
+    pub value: T,
+    pub variance: f64,
+}
+
+// Variance added by a gadget product with `rows` rows of the given noise variance
+fn gadget_product_variance(rows: usize, row_variance: f64) -> f64 {
+    // powi needs std, so build 2^GADGET_LOG_BASE from a 2^(GADGET_LOG_BASE - 1) that fits in u128.
+    let base = 2.0 * (1u128 << (GADGET_LOG_BASE - 1)) as f64;
+    let digit_square = base * base / 3.0;
+    rows as f64 * DEGREE as f64 * digit_square * row_variance
+}
+
+impl<T> Tracked<T> {
+    #[cfg(feature = "std")]
+    pub fn log2_stddev(&self) -> f64 {
+        self.variance.log2() / 2.0
+    }
+}
+
+impl Tracked<RlweCiphertext> {
+    pub fn encrypt<R: Rng + ?Sized>(
+        key: &SecretKey,
+        message: &PowerPoly,
+        rng: &mut R,
+    ) -> Tracked<RlweCiphertext> {
+        Tracked {
+            value: RlweCiphertext::encrypt(key, message, rng),
+            variance: ERROR_VARIANCE,
+        }
+    }
+
+    // Assumes a key generated by KeySwitchingKey::generate.
+    pub fn key_switch(&self, key: &KeySwitchingKey) -> Tracked<RlweCiphertext> {
+        Tracked {
+            value: key.switch(&self.value),
+            variance: self.variance + gadget_product_variance(GADGET_LEVELS, ERROR_VARIANCE),
+        }
+    }
+}
+
+// The estimates assume that the RGSW message has at most one nonzero coefficient, which is
+// plus or minus one, as for bits and monomials.
+impl Tracked<RgswCiphertext> {
+    pub fn encrypt<R: Rng + ?Sized>(
+        key: &SecretKey,
+        message: &PowerPoly,
+        rng: &mut R,
+    ) -> Tracked<RgswCiphertext> {
+        Tracked {
+            value: RgswCiphertext::encrypt(key, message, rng),
+            variance: ERROR_VARIANCE,
+        }
+    }
+
+    pub fn external_product(
+        &self,
+        ciphertext: &Tracked<RlweCiphertext>,
+    ) -> Tracked<RlweCiphertext> {
+        Tracked {
+            value: self.value.external_product(&ciphertext.value),
+            variance: ciphertext.variance
+                + gadget_product_variance(2 * GADGET_LEVELS, self.variance),
+        }
+    }
+
+    pub fn cmux(
+        &self,
+        if_false: &Tracked<RlweCiphertext>,
+        if_true: &Tracked<RlweCiphertext>,
+    ) -> Tracked<RlweCiphertext> {
+        Tracked {
+            value: self.value.cmux(&if_false.value, &if_true.value),
+            variance: if_false.variance.max(if_true.variance)
+                + gadget_product_variance(2 * GADGET_LEVELS, self.variance),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::error::Error;
+use crate::parallel::{join, Schedule};
+use core::ops::Range;
+
+// Cache-oblivious matrix transposition. The blocks are split recursively along their longer side
+// until they have at most block_size elements. A majors x minors matrix is stored row-major, with
+// `stride` elements from the start of one row to the start of the next.
+
+// Default number of elements in a base-case block
+pub const DEFAULT_BLOCK_SIZE: usize = 256;
+
+// The number of elements spanned by a majors x minors matrix with the given stride
+fn extent(majors: usize, minors: usize, stride: usize) -> usize {
+    if majors == 0 || minors == 0 {
+        0
+    } else {
+        (majors - 1) * stride + minors
+    }
+}
+
+// Checks that data can hold a majors x minors matrix with the given stride.
+fn check_matrix<T>(majors: usize, minors: usize, data: &[T], stride: usize) -> Result<(), Error> {
+    if stride < minors {
+        return Err(Error::ParameterMismatch(
+            "stride is less than the row length",
+        ));
+    }
+    let expected = extent(majors, minors, stride);
+    if data.len() < expected {
+        return Err(Error::LengthMismatch {
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+fn check_block_size(block_size: usize) -> Result<(), Error> {
+    if block_size == 0 {
+        return Err(Error::ParameterMismatch("block size is zero"));
+    }
+    Ok(())
+}
+
+struct OutOfPlace<'a, T> {
+    src: &'a [T],
+    src_stride: usize,
+    dest_stride: usize,
+    block_size: usize,
+}
+
+impl<T> OutOfPlace<'_, T>
+where
+    T: Clone + Send + Sync,
+{
+    // Transposes the block of src given by the ranges. dest starts at row minors.start of the
+    // result.
+    fn run(&self, dest: &mut [T], majors: Range<usize>, minors: Range<usize>) {
+        let my_majors = majors.len();
+        let my_minors = minors.len();
+        if my_majors * my_minors <= self.block_size {
+            for maj in majors {
+                let row = &self.src[maj * self.src_stride..];
+                for (i, min) in minors.clone().enumerate() {
+                    dest[i * self.dest_stride + maj] = row[min].clone();
+                }
+            }
+        } else if my_majors >= my_minors {
+            let middle = majors.start + my_majors / 2;
+            self.run(dest, majors.start..middle, minors.clone());
+            self.run(dest, middle..majors.end, minors);
+        } else {
+            // Splitting the minors splits dest into disjoint rows.
+            let middle = minors.start + my_minors / 2;
+            let (dest_begin, dest_end) = dest.split_at_mut(my_minors / 2 * self.dest_stride);
+            join(
+                Schedule::Parallel,
+                my_majors * my_minors,
+                || self.run(dest_begin, majors.clone(), minors.start..middle),
+                || self.run(dest_end, majors.clone(), middle..minors.end),
+            );
+        }
+    }
+}
+
+// Writes the transpose of the majors x minors matrix src, with stride src_stride, to the
+// minors x majors matrix dest, with stride dest_stride.
+pub fn transpose_strided<T>(
+    majors: usize,
+    minors: usize,
+    src: &[T],
+    src_stride: usize,
+    dest: &mut [T],
+    dest_stride: usize,
+    block_size: usize,
+) -> Result<(), Error>
+where
+    T: Clone + Send + Sync,
+{
+    check_matrix(majors, minors, src, src_stride)?;
+    check_matrix(minors, majors, dest, dest_stride)?;
+    check_block_size(block_size)?;
+    let transposer = OutOfPlace {
+        src,
+        src_stride,
+        dest_stride,
+        block_size,
+    };
+    transposer.run(dest, 0..majors, 0..minors);
+    Ok(())
+}
+
+// Writes the transpose of the densely stored majors x minors matrix src to dest.
+pub fn transpose<T>(majors: usize, minors: usize, src: &[T], dest: &mut [T]) -> Result<(), Error>
+where
+    T: Clone + Send + Sync,
+{
+    for len in [src.len(), dest.len()].iter() {
+        if *len != majors * minors {
+            return Err(Error::LengthMismatch {
+                expected: majors * minors,
+                actual: *len,
+            });
+        }
+    }
+    transpose_strided(
+        majors,
+        minors,
+        src,
+        minors,
+        dest,
+        majors,
+        DEFAULT_BLOCK_SIZE,
+    )
+}
+
+struct InPlace {
+    stride: usize,
+    block_size: usize,
+}
+
+impl InPlace {
+    // Swaps the entries (i, j) and (j, i) for i in rows and j in cols, where all of rows lies
+    // before all of cols.
+    fn swap<T>(&self, data: &mut [T], rows: Range<usize>, cols: Range<usize>) {
+        if rows.len() * cols.len() <= self.block_size {
+            for i in rows {
+                for j in cols.clone() {
+                    data.swap(i * self.stride + j, j * self.stride + i);
+                }
+            }
+        } else if rows.len() >= cols.len() {
+            let middle = rows.start + rows.len() / 2;
+            self.swap(data, rows.start..middle, cols.clone());
+            self.swap(data, middle..rows.end, cols);
+        } else {
+            let middle = cols.start + cols.len() / 2;
+            self.swap(data, rows.clone(), cols.start..middle);
+            self.swap(data, rows, middle..cols.end);
+        }
+    }
+
+    // Transposes the diagonal block given by range.
+    fn run<T>(&self, data: &mut [T], range: Range<usize>) {
+        if range.len() * range.len() <= self.block_size {
+            self.swap_diagonal(data, range);
+        } else {
+            let middle = range.start + range.len() / 2;
+            self.run(data, range.start..middle);
+            self.run(data, middle..range.end);
+            self.swap(data, range.start..middle, middle..range.end);
+        }
+    }
+
+    fn swap_diagonal<T>(&self, data: &mut [T], range: Range<usize>) {
+        for i in range.clone() {
+            for j in i + 1..range.end {
+                data.swap(i * self.stride + j, j * self.stride + i);
+            }
+        }
+    }
+}
+
+// Transposes the n x n matrix data, with the given stride, in place.
+pub fn transpose_in_place<T>(
+    n: usize,
+    data: &mut [T],
+    stride: usize,
+    block_size: usize,
+) -> Result<(), Error> {
+    check_matrix(n, n, data, stride)?;
+    check_block_size(block_size)?;
+    InPlace { stride, block_size }.run(data, 0..n);
+    Ok(())
+}
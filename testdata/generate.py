@@ -0,0 +1,67 @@
+#!/usr/bin/env python3
+# Generates the known-answer test vectors in crt.kat, independently of the Rust implementation.
+# The polynomials are evaluated with a plain radix-2 negacyclic FFT over Python integers.
+#
+# Usage: python3 testdata/generate.py > testdata/crt.kat
+
+DEGREE = 2**14
+MODULUS = 2**384 - 1081343
+INDEX_TH_ROOT = 1053046320810374670051386479886365060104392590871858199730685648701498648418052922079079421152377431379245380088
+
+# Only a subset of each output is recorded, to keep the file small.
+SAMPLES = sorted({(129 * k) % DEGREE for k in range(128)} | {DEGREE - 1})
+
+
+def fft(values, root):
+    # Evaluates sum_i values[i] X^i at root^k for all k.
+    n = len(values)
+    if n == 1:
+        return list(values)
+    even = fft(values[0::2], root * root % MODULUS)
+    odd = fft(values[1::2], root * root % MODULUS)
+    result = [0] * n
+    twiddle = 1
+    for k in range(n // 2):
+        x = twiddle * odd[k] % MODULUS
+        result[k] = (even[k] + x) % MODULUS
+        result[k + n // 2] = (even[k] - x) % MODULUS
+        twiddle = twiddle * root % MODULUS
+    return result
+
+
+def power_to_crt(coeffs):
+    # Slot s is the evaluation at INDEX_TH_ROOT^(2s + 1).
+    twisted = [c * pow(INDEX_TH_ROOT, i, MODULUS) % MODULUS for i, c in enumerate(coeffs)]
+    return fft(twisted, INDEX_TH_ROOT**2 % MODULUS)
+
+
+def crt_to_power(slots):
+    inv_root = pow(INDEX_TH_ROOT, -1, MODULUS)
+    inv_degree = pow(DEGREE, -1, MODULUS)
+    twisted = fft(slots, inv_root**2 % MODULUS)
+    return [c * inv_degree * pow(inv_root, i, MODULUS) % MODULUS for i, c in enumerate(twisted)]
+
+
+def powers(base):
+    return [pow(base, i, MODULUS) for i in range(DEGREE)]
+
+
+def emit(name, values):
+    print(f"[{name}]")
+    for index in SAMPLES:
+        print(f"{index} {values[index]:x}")
+    print()
+
+
+assert pow(INDEX_TH_ROOT, DEGREE, MODULUS) == MODULUS - 1
+
+print("# Generated by testdata/generate.py. Each section lists <index> <hex value> pairs.")
+print()
+# Coefficients 3^i, evaluated in CRT slot order
+emit("power_to_crt powers_of_3", power_to_crt(powers(3)))
+# Coefficients i
+emit("power_to_crt linear", power_to_crt(list(range(DEGREE))))
+# Coefficients -1 = MODULUS - 1
+emit("power_to_crt minus_one", power_to_crt([MODULUS - 1] * DEGREE))
+# Slots 5^s, interpolated to coefficients
+emit("crt_to_power powers_of_5", crt_to_power(powers(5)))